@@ -1,6 +1,9 @@
 use derive_more::From;
 use log::*;
 use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use std::time::Duration;
+use yew::services::timeout::{TimeoutService, TimeoutTask};
 use yew::worker::*;
 use yewtil::NeqAssign;
 
@@ -8,6 +11,15 @@ use yewtil::NeqAssign;
 pub use yew::agent::{Dispatched, Dispatcher};
 
 use crate::agents::game_ws_mgr::*;
+use crate::agents::notifications::*;
+use crate::game::aria::{self, Announcement};
+use crate::game::recording::{Direction, Recording};
+use crate::game::GameState;
+use crate::wire;
+
+/// The delay before reconnecting, multiplied by the number of attempts.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
 
 #[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
 pub struct GameData {
@@ -17,21 +29,57 @@ pub struct GameData {
 pub struct GameMgr {
     link: AgentLink<Self>,
     subscribers: Vec<HandlerId>,
+    notification_bus: Dispatcher<NotificationBus>,
 
     ws_agent: Box<dyn Bridge<GameWsMgr>>,
     ws_status: WebSocketStatus,
+    /// The game to stay connected to, until left.
+    connection: Option<GameWsConnectionInfo>,
+    reconnect_attempts: u32,
+    _reconnect_task: Option<TimeoutTask>,
+    /// The player the game is seen by, to describe what happens to them.
+    player_id: String,
 
     data: GameData,
+    game: Rc<GameState>,
+    /// The messages exchanged since the last connection.
+    recording: Recording,
 }
 
 #[derive(Debug, Clone)]
 pub enum GameMgrRequest {
     EnsureConnected(GameWsConnectionInfo),
+    /// Leaves the game, closing the connection.
+    Leave,
+    /// Gives up the seat of the player in the round, then leaves the game.
+    LeaveRound,
+    /// Removes a player from the round, which only its creator can do.
+    KickPlayer(String),
+    /// Tells the server that the player did one of the awaited actions.
+    PerformAction(wire::PerformAction),
+    SendChatMessage(wire::SendChatMessage),
+    /// Watches the game as one of the players, or as a spectator with `None`.
+    WatchSeat(Option<String>),
+    GetRecording,
+    /// Asks for the current state of the game and of the connection.
+    #[cfg_attr(not(feature = "debug-console"), allow(dead_code))]
+    GetState,
+    /// Points out a component of the table, or stops with `None`.
+    #[cfg_attr(not(feature = "debug-console"), allow(dead_code))]
+    Highlight(Option<wire::ComponentId>),
 }
 
 #[derive(Debug, Clone)]
 pub enum GameMgrResponse {
     WebSocketStatusChanged(WebSocketStatus),
+    GameStateChanged(Rc<GameState>),
+    /// Something to read out to screen reader users.
+    Announcement(Announcement),
+    ChatMessageReceived(wire::ChatMessage),
+    Recording(Rc<Recording>),
+    Highlighted(Option<wire::ComponentId>),
+    /// The player was removed from the game by its creator.
+    SessionEnded,
 }
 
 #[derive(From, Debug)]
@@ -43,6 +91,7 @@ pub enum Msg {
 #[derive(Debug)]
 pub enum Command {
     Update,
+    Reconnect,
 }
 
 #[derive(Debug, From)]
@@ -60,14 +109,23 @@ impl Agent for GameMgr {
     fn create(link: AgentLink<Self>) -> Self {
         link.send_message(Command::Update);
         let ws_msg_callback = link.callback(Event::WebSocketMessage);
+        let mut ws_agent = GameWsMgr::bridge(ws_msg_callback);
+        ws_agent.send(GameWsRequest::SubscribeToSentMessages(true));
         Self {
             link,
             subscribers: Vec::with_capacity(10),
+            notification_bus: NotificationBus::dispatcher(),
 
-            ws_agent: GameWsMgr::bridge(ws_msg_callback),
+            ws_agent,
             ws_status: WebSocketStatus::NotConnected,
+            connection: None,
+            reconnect_attempts: 0,
+            _reconnect_task: None,
+            player_id: String::new(),
 
             data: GameData::default(),
+            game: Rc::new(GameState::default()),
+            recording: Recording::default(),
         }
     }
 
@@ -77,28 +135,57 @@ impl Agent for GameMgr {
                 Command::Update => {
                     self.ws_agent.send(GameWsRequest::GetWebSocketStatus);
                 }
+                Command::Reconnect => {
+                    self._reconnect_task = None;
+                    if let Some(info) = &self.connection {
+                        self.ws_agent.send(GameWsRequest::JoinRound(info.clone()));
+                    }
+                }
             },
             Msg::Event(event) => match event {
                 Event::WebSocketMessage(ws_msg) => {
                     let status_changed = match ws_msg {
                         GameWsResponse::Connecting(info) => {
+                            // The server sends the whole state of the game when joining
+                            self.game = Rc::new(GameState::default());
+                            self.player_id = info.player_id.clone();
+                            // Reconnecting to the same game goes on with its recording
+                            let header = &self.recording.header;
+                            if header.game_id != info.game_id || header.player_id != info.player_id
+                            {
+                                self.recording = Recording::new(&info.game_id, &info.player_id);
+                            }
+                            self.broadcast_to_subscribers(GameMgrResponse::GameStateChanged(
+                                self.game.clone(),
+                            ));
                             self.update_ws_status(WebSocketStatus::Pending(info))
                         }
                         GameWsResponse::Connected(info) => {
+                            self.reconnect_attempts = 0;
+                            self.announce(Announcement::polite("Connected to the game"));
                             self.update_ws_status(WebSocketStatus::Connected(info))
                         }
-                        GameWsResponse::Closed => {
+                        GameWsResponse::Closed(cause) => {
+                            self.closed(cause);
                             self.update_ws_status(WebSocketStatus::NotConnected)
                         }
                         GameWsResponse::FailedToConnect(_reason) => {
+                            self.announce(Announcement::assertive("Failed to connect to the game"));
                             self.update_ws_status(WebSocketStatus::NotConnected)
                         }
                         GameWsResponse::ErrorOccurred => {
+                            // Announced by the close which follows the error
+                            self.schedule_reconnect();
                             self.update_ws_status(WebSocketStatus::NotConnected)
                         }
-                        GameWsResponse::Sent(_) => unreachable!("Never subscribed"),
+                        GameWsResponse::Sent(ws_msg) => {
+                            self.record(Direction::Sent, &ws_msg.0);
+                            false
+                        }
                         GameWsResponse::Received(ws_msg) => self.handle_ws_msg(ws_msg),
                         GameWsResponse::ReceivedError(_error) => false,
+                        #[cfg(feature = "debug-console")]
+                        GameWsResponse::Stats(_) => false,
                         GameWsResponse::WebSocketStatus(status) => self.update_ws_status(status),
                     };
                     if status_changed {
@@ -115,7 +202,54 @@ impl Agent for GameMgr {
         trace!("Notification received from '{:?}': {:?}", sender, input);
         match input {
             GameMgrRequest::EnsureConnected(conn_info) => {
+                if self.connection.as_ref() != Some(&conn_info) {
+                    self.connection = Some(conn_info.clone());
+                    self.reconnect_attempts = 0;
+                }
                 self.ws_agent.send(GameWsRequest::JoinRound(conn_info));
+                self.link
+                    .respond(sender, GameMgrResponse::GameStateChanged(self.game.clone()));
+            }
+            GameMgrRequest::Leave => self.leave(),
+            GameMgrRequest::LeaveRound => {
+                self.ws_agent
+                    .send(GameWsRequest::Send(WsRequest(wire::Message::LeaveRound)));
+                self.leave();
+                self.clear_session();
+            }
+            GameMgrRequest::KickPlayer(player_id) => {
+                let message = wire::KickPlayer { player_id };
+                self.ws_agent
+                    .send(GameWsRequest::Send(WsRequest(message.into())));
+            }
+            GameMgrRequest::PerformAction(action) => {
+                self.ws_agent
+                    .send(GameWsRequest::Send(WsRequest(action.into())));
+            }
+            GameMgrRequest::SendChatMessage(message) => {
+                self.ws_agent
+                    .send(GameWsRequest::Send(WsRequest(message.into())));
+            }
+            GameMgrRequest::WatchSeat(player_id) => {
+                let message = wire::WatchSeat { player_id };
+                self.ws_agent
+                    .send(GameWsRequest::Send(WsRequest(message.into())));
+            }
+            GameMgrRequest::GetRecording => {
+                let recording = Rc::new(self.recording.clone());
+                self.link
+                    .respond(sender, GameMgrResponse::Recording(recording));
+            }
+            GameMgrRequest::GetState => {
+                self.link
+                    .respond(sender, GameMgrResponse::GameStateChanged(self.game.clone()));
+                self.link.respond(
+                    sender,
+                    GameMgrResponse::WebSocketStatusChanged(self.ws_status.clone()),
+                );
+            }
+            GameMgrRequest::Highlight(id) => {
+                self.broadcast_to_subscribers(GameMgrResponse::Highlighted(id));
             }
         }
     }
@@ -135,6 +269,12 @@ impl Agent for GameMgr {
 
 type WsStatusChanged = bool;
 
+impl NotificationSender for GameMgr {
+    fn notification_bus(&mut self) -> &mut Dispatcher<NotificationBus> {
+        &mut self.notification_bus
+    }
+}
+
 impl GameMgr {
     fn broadcast_to_subscribers(&mut self, output: GameMgrResponse) {
         for sub in self.subscribers.iter() {
@@ -146,8 +286,95 @@ impl GameMgr {
         self.ws_status.neq_assign(status)
     }
 
+    fn closed(&mut self, cause: CloseCause) {
+        match &cause {
+            CloseCause::Client(_) => {
+                self.announce(Announcement::polite("Disconnected from the game"));
+            }
+            CloseCause::Server(close) => {
+                let message = close.user_message();
+                self.announce(Announcement::assertive(message.clone()));
+                if close.allows_reconnecting() {
+                    self.notify_info(message);
+                } else {
+                    self.notify_warn(message);
+                }
+            }
+            CloseCause::Lost => {
+                self.announce(Announcement::assertive("Lost the connection to the game"));
+            }
+        }
+
+        match cause {
+            cause if cause.should_reconnect() => self.schedule_reconnect(),
+            CloseCause::Server(close) if close.reason == Some(wire::CloseReason::Kicked) => {
+                // Removed from the round, nothing is left to play in it.
+                self.clear_session();
+                self.broadcast_to_subscribers(GameMgrResponse::SessionEnded);
+            }
+            _ => {
+                // Closed on purpose, the game is not joined again. When the
+                // round ended, its final state and winners stay in sight.
+                self.connection = None;
+                self._reconnect_task = None;
+            }
+        }
+    }
+
+    fn leave(&mut self) {
+        self.connection = None;
+        self._reconnect_task = None;
+        let close = wire::Close::new(wire::CloseReason::Leaving);
+        self.ws_agent.send(GameWsRequest::Close(close));
+    }
+
+    /// Forgets the game, which is not joined again.
+    fn clear_session(&mut self) {
+        self.connection = None;
+        self._reconnect_task = None;
+        self.game = Rc::new(GameState::default());
+        self.broadcast_to_subscribers(GameMgrResponse::GameStateChanged(self.game.clone()));
+    }
+
+    /// Joins the game again after a while, giving up after a few attempts.
+    fn schedule_reconnect(&mut self) {
+        if self.connection.is_none() || self._reconnect_task.is_some() {
+            return;
+        }
+        if self.reconnect_attempts >= MAX_RECONNECT_ATTEMPTS {
+            self.notify_error("Failed to reconnect to the game.");
+            return;
+        }
+        self.reconnect_attempts += 1;
+        let delay = RECONNECT_DELAY * self.reconnect_attempts;
+        debug!("Reconnecting in {:?}.", delay);
+        self._reconnect_task =
+            Some(TimeoutService::new().spawn(delay, self.link.callback(|_| Command::Reconnect)));
+    }
+
+    fn announce(&mut self, announcement: Announcement) {
+        self.broadcast_to_subscribers(GameMgrResponse::Announcement(announcement));
+    }
+
+    fn record(&mut self, direction: Direction, message: &wire::Message) {
+        self.recording
+            .record(js_sys::Date::now(), direction, message.clone());
+    }
+
     fn handle_ws_msg(&mut self, ws_msg: WsResponse) -> WsStatusChanged {
         log::debug!("Received: {:?}", ws_msg);
+        self.record(Direction::Received, &ws_msg.0);
+        if let wire::Message::ChatMessage(message) = &ws_msg.0 {
+            self.broadcast_to_subscribers(GameMgrResponse::ChatMessageReceived(message.clone()));
+            return false;
+        }
+        let before = self.game.clone();
+        if Rc::make_mut(&mut self.game).apply(&ws_msg.0) {
+            self.broadcast_to_subscribers(GameMgrResponse::GameStateChanged(self.game.clone()));
+        }
+        for announcement in aria::announcements(&ws_msg.0, &before, &self.game, &self.player_id) {
+            self.announce(announcement);
+        }
         false
     }
 }
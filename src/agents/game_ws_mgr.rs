@@ -5,16 +5,16 @@ use derive_more::From;
 use log::*;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use yew::format::Json;
 use yew::prelude::*;
-use yew::services::websocket::{
-    WebSocketService, WebSocketStatus as YewWebSocketStatus, WebSocketTask,
-};
 use yew::worker::*;
 
 // Re-export this for convenience
 pub use yew::agent::{Bridge, Bridged, Dispatched, Dispatcher};
 
+#[cfg(feature = "debug-console")]
+use super::ws_stats::TrafficStats;
+use crate::services::api::ApiUrl;
+use crate::services::transport::*;
 use crate::wire;
 
 pub struct GameWsMgr {
@@ -23,14 +23,43 @@ pub struct GameWsMgr {
     subscribers: Vec<HandlerId>,
     sent_subscribers: Vec<HandlerId>,
 
-    ws_service: WebSocketService,
+    socket: GameSocket,
+}
+
+/// The connection to the game server, apart from the agent so that it can be
+/// driven by tests through a `Loopback`. What the subscribers must be told is
+/// queued until taken by the agent.
+struct GameSocket {
+    transport: Box<dyn Transport>,
     ws: WebSocketConnection,
+    #[cfg(feature = "debug-console")]
+    stats: TrafficStats,
+
+    on_frame: Callback<Result<Frame>>,
+    on_status: Callback<TransportStatus>,
+    outputs: Vec<GameWsResponse>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct GameWsConnectionInfo {
     pub game_id: String,
+    /// Player ID of the player, empty for a spectator.
     pub player_id: String,
+    /// Name of the spectator, when the game is only watched without taking a
+    /// seat.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spectator: Option<String>,
+}
+
+impl GameWsConnectionInfo {
+    /// Who the connection is for, e.g. "p1" or "spectator Zoé".
+    #[cfg_attr(not(feature = "debug-console"), allow(dead_code))]
+    pub fn identity(&self) -> String {
+        match &self.spectator {
+            Some(name) => format!("spectator {}", name),
+            None => self.player_id.clone(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -38,33 +67,43 @@ pub enum WebSocketConnection {
     None,
     Pending {
         info: GameWsConnectionInfo,
-        task: WebSocketTask,
+        connection: Box<dyn Connection>,
     },
     Connected {
         info: GameWsConnectionInfo,
-        task: WebSocketTask,
+        connection: Box<dyn Connection>,
+        encoding: wire::Encoding,
     },
 }
 
 #[derive(Debug, From)]
 pub enum Msg {
     #[from]
-    WsNotification(YewWebSocketStatus),
-    WsReceived(Result<WsResponse>), // TODO: Try use Cow or Rc
+    WsNotification(TransportStatus),
+    WsReceived(ReceivedFrame), // TODO: Try use Cow or Rc
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Debug)]
 pub enum GameWsRequest {
-    CloseSocket,
+    /// current one. Used to talk to an in-memory server, e.g. by the tests.
+    /// current one. Used by tests to talk to an in-memory server.
+    #[cfg(any(test, feature = "debug-console"))]
+    #[cfg_attr(not(test), allow(dead_code))]
+    UseTransport(Box<dyn Transport>),
+    /// Tells the server why the connection is closed, then closes it.
+    Close(wire::Close),
     JoinRound(GameWsConnectionInfo),
     Send(WsRequest),
     GetWebSocketStatus,
     SubscribeToSentMessages(bool),
+    /// Asks for the statistics on the traffic of the connection.
+    #[cfg(feature = "debug-console")]
+    GetStats,
 }
 
 #[derive(Debug, Clone, From)]
 pub enum GameWsResponse {
-    Closed,
+    Closed(CloseCause),
     Connected(GameWsConnectionInfo),
     Connecting(GameWsConnectionInfo),
     ErrorOccurred,
@@ -72,10 +111,35 @@ pub enum GameWsResponse {
     Received(WsResponse),
     ReceivedError(String), // TODO: Refine error type
     Sent(WsRequest),
+    #[cfg(feature = "debug-console")]
+    Stats(TrafficStats),
     #[from]
     WebSocketStatus(WebSocketStatus),
 }
 
+/// Why the connection was closed, to tell intentional closes from failures.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CloseCause {
+    /// Closed by the client, e.g. when leaving the game.
+    Client(wire::Close),
+    /// Closed on purpose by the server, which sent a `CLOSE` message first.
+    Server(wire::Close),
+    /// The connection was lost, without any `CLOSE` message.
+    Lost,
+}
+
+impl CloseCause {
+    /// Whether the game should be joined again, which is only the case when
+    /// the connection was lost or the server asked for it.
+    pub fn should_reconnect(&self) -> bool {
+        match self {
+            CloseCause::Client(_) => false,
+            CloseCause::Server(close) => close.allows_reconnecting(),
+            CloseCause::Lost => true,
+        }
+    }
+}
+
 /// Represents the state of the WebSocket. Differs from WebSocketConnection in
 /// that this is sent to subscribers, when WebSocketConnection holds the actual
 /// connection.
@@ -86,6 +150,8 @@ pub enum WebSocketStatus {
     Connected(GameWsConnectionInfo),
 }
 
+// Only used by the debug console.
+#[cfg_attr(not(feature = "debug-console"), allow(dead_code))]
 impl WebSocketStatus {
     pub fn is_pending(&self) -> bool {
         match self {
@@ -120,6 +186,55 @@ pub struct WsRequest(pub wire::Message);
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WsResponse(pub wire::Message);
 
+/// A frame received on the WebSocket. Text frames are always decoded as JSON,
+/// whereas binary frames are decoded using the preferred binary encoding, if
+/// one is enabled.
+#[derive(Debug)]
+pub struct ReceivedFrame {
+    data: Result<WsResponse>,
+    encoding: wire::Encoding,
+    /// The size of the frame, in bytes.
+    #[cfg(feature = "debug-console")]
+    size: usize,
+}
+
+impl From<Result<Frame>> for ReceivedFrame {
+    fn from(frame: Result<Frame>) -> Self {
+        #[cfg(feature = "debug-console")]
+        let size = frame.as_ref().map_or(0, Frame::size);
+        match frame {
+            Ok(Frame::Text(text)) => {
+                let encoding = wire::Encoding::Json;
+                ReceivedFrame {
+                    data: encoding.decode(text.as_bytes()).map(WsResponse),
+                    encoding,
+                    #[cfg(feature = "debug-console")]
+                    size,
+                }
+            }
+            Ok(Frame::Binary(bytes)) => {
+                let encoding = if wire::Encoding::PREFERRED.is_binary() {
+                    wire::Encoding::PREFERRED
+                } else {
+                    wire::Encoding::Json
+                };
+                ReceivedFrame {
+                    data: encoding.decode(&bytes).map(WsResponse),
+                    encoding,
+                    #[cfg(feature = "debug-console")]
+                    size,
+                }
+            }
+            Err(e) => ReceivedFrame {
+                data: Err(e),
+                encoding: wire::Encoding::Json,
+                #[cfg(feature = "debug-console")]
+                size,
+            },
+        }
+    }
+}
+
 impl Agent for GameWsMgr {
     type Reach = Context<Self>;
     type Message = Msg;
@@ -127,162 +242,586 @@ impl Agent for GameWsMgr {
     type Output = GameWsResponse;
 
     fn create(link: AgentLink<Self>) -> Self {
+        let socket = GameSocket::new(
+            Box::new(WebSocketTransport::new()),
+            link.callback(|frame: Result<Frame>| Msg::WsReceived(frame.into())),
+            link.callback(Msg::WsNotification),
+        );
         GameWsMgr {
             link,
             subscribers: Vec::with_capacity(10), // TODO: Tune capacities
             sent_subscribers: Vec::with_capacity(10), // TODO: Tune capacities
-            ws_service: WebSocketService::new(),
-            ws: WebSocketConnection::None,
+            socket,
         }
     }
 
     fn update(&mut self, msg: Self::Message) {
+        self.socket.update(msg);
+        self.dispatch_outputs();
+    }
+
+    fn handle_input(&mut self, input: Self::Input, sender: HandlerId) {
+        match input {
+            GameWsRequest::SubscribeToSentMessages(subscribe) => {
+                let sender_position = self.sent_subscribers.iter().position(|id| *id == sender);
+                if subscribe {
+                    if sender_position.is_none() {
+                        trace!("New WS sent subscriber: {:?}", sender);
+                        self.sent_subscribers.push(sender);
+                    }
+                } else {
+                    if let Some(pos) = sender_position {
+                        self.sent_subscribers.swap_remove(pos);
+                        trace!("WS sent subscriber disconnected: {:?}", sender);
+                    } else {
+                        warn!("Disconnection but no associated subscriber.");
+                    }
+                }
+            }
+            input => {
+                if let Some(response) = self.socket.handle_input(input) {
+                    self.link.respond(sender, response);
+                }
+                self.dispatch_outputs();
+            }
+        }
+    }
+
+    fn connected(&mut self, id: HandlerId) {
+        trace!("New connection: {:?}", id);
+        if !self.subscribers.contains(&id) {
+            self.subscribers.push(id);
+        }
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        // Stop sending it the sent messages too, if it asked for them
+        if let Some(pos) = self.sent_subscribers.iter().position(|x| *x == id) {
+            self.sent_subscribers.swap_remove(pos);
+        }
+
+        // Remove the subscriber (efficiently, hence swap_remove)
+        if let Some(pos) = self.subscribers.iter().position(|x| *x == id) {
+            self.subscribers.swap_remove(pos);
+            trace!("Subscriber disconnected: {:?}", id);
+        } else {
+            warn!("Disconnection but no associated subscriber.");
+        }
+    }
+
+    fn destroy(&mut self) {
+        self.socket.leave();
+    }
+}
+
+impl GameWsMgr {
+    /// Tells the subscribers what happened on the connection: the sent
+    /// messages only go to the ones which asked for them.
+    fn dispatch_outputs(&mut self) {
+        for output in self.socket.take_outputs() {
+            let subscribers = match output {
+                GameWsResponse::Sent(_) => &self.sent_subscribers,
+                _ => &self.subscribers,
+            };
+            for sub in subscribers.iter() {
+                self.link.respond(*sub, output.clone());
+            }
+        }
+    }
+}
+
+impl GameSocket {
+    fn new(
+        transport: Box<dyn Transport>,
+        on_frame: Callback<Result<Frame>>,
+        on_status: Callback<TransportStatus>,
+    ) -> Self {
+        GameSocket {
+            transport,
+            ws: WebSocketConnection::None,
+            #[cfg(feature = "debug-console")]
+            stats: TrafficStats::new(now()),
+            on_frame,
+            on_status,
+            outputs: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, msg: Msg) {
         match msg {
             Msg::WsNotification(status) => {
                 let current_ws = std::mem::replace(&mut self.ws, WebSocketConnection::None);
                 let (ws, out) = match (current_ws, &status) {
-                    (WebSocketConnection::Pending { info, task }, YewWebSocketStatus::Opened) => {
+                    (
+                        WebSocketConnection::Pending { info, connection },
+                        TransportStatus::Opened,
+                    ) => {
                         let info_clone = info.clone();
                         (
-                            WebSocketConnection::Connected { info, task },
+                            // JSON is used until the server answers in binary
+                            WebSocketConnection::Connected {
+                                info,
+                                connection,
+                                encoding: wire::Encoding::Json,
+                            },
                             GameWsResponse::Connected(info_clone),
                         )
                     }
-                    (_, YewWebSocketStatus::Opened) => {
+                    (_, TransportStatus::Opened) => {
                         log::error!("WebSocket opened but no pending connection, ignoring.");
                         (WebSocketConnection::None, GameWsResponse::ErrorOccurred)
                     }
-                    (_, YewWebSocketStatus::Closed) => {
-                        (WebSocketConnection::None, GameWsResponse::Closed)
-                    }
-                    (_, YewWebSocketStatus::Error) => {
+                    (_, TransportStatus::Closed) => (
+                        WebSocketConnection::None,
+                        GameWsResponse::Closed(CloseCause::Lost),
+                    ),
+                    (_, TransportStatus::Error) => {
                         (WebSocketConnection::None, GameWsResponse::ErrorOccurred)
                     }
                 };
                 self.ws = ws;
-                self.broadcast_to_subscribers(out);
+                self.status_changed();
+                self.outputs.push(out);
             }
-            Msg::WsReceived(data) => {
+            Msg::WsReceived(frame) => {
+                #[cfg(feature = "debug-console")]
+                let size = frame.size;
+                let ReceivedFrame { data, encoding, .. } = frame;
+                if let WebSocketConnection::Connected {
+                    encoding: current_encoding,
+                    ..
+                } = &mut self.ws
+                {
+                    if *current_encoding != encoding && data.is_ok() {
+                        info!("Server switched the WebSocket to {:?}.", encoding);
+                        *current_encoding = encoding;
+                    }
+                }
+
+                #[cfg(feature = "debug-console")]
+                match &data {
+                    Ok(data) => self.stats.received(data.0.type_tag(), size),
+                    Err(_) => self.stats.decode_failed(size),
+                }
                 let out = match data {
                     Ok(data) => GameWsResponse::Received(data),
                     Err(err) => GameWsResponse::ReceivedError(err.to_string()),
                 };
-                self.broadcast_to_subscribers(out);
+                let server_close = match &out {
+                    GameWsResponse::Received(WsResponse(wire::Message::Close(close))) => {
+                        Some(close.clone())
+                    }
+                    _ => None,
+                };
+                self.outputs.push(out);
+                if let Some(close) = server_close {
+                    info!("Server closed the WebSocket: {:?}", close);
+                    self.close(CloseCause::Server(close));
+                }
             }
         }
     }
 
-    fn handle_input(&mut self, input: Self::Input, sender: HandlerId) {
+    /// Handles a request, returning what to answer to its sender, if anything.
+    fn handle_input(&mut self, input: GameWsRequest) -> Option<GameWsResponse> {
         match input {
-            GameWsRequest::JoinRound(conn_info) => {
-                match &self.ws {
-                    // Already connected with the right game and player IDs.
-                    WebSocketConnection::Pending { info, .. }
-                    | WebSocketConnection::Connected { info, .. }
-                        if *info == conn_info =>
-                    {
-                        self.link
-                            .respond(sender, WebSocketStatus::from(&self.ws).into());
-                    }
-                    // Otherwise, not connected or different connection info.
-                    _ => {
-                        let GameWsConnectionInfo { game_id, player_id } = conn_info;
-                        if let Err(e) = self.join_round(game_id, player_id) {
-                            self.link
-                                .respond(sender, GameWsResponse::FailedToConnect(e.to_string()));
-                        }
-                    }
+            GameWsRequest::JoinRound(conn_info) => match &self.ws {
+                // Already connected with the right game and player IDs.
+                WebSocketConnection::Pending { info, .. }
+                | WebSocketConnection::Connected { info, .. }
+                    if *info == conn_info =>
+                {
+                    Some(WebSocketStatus::from(&self.ws).into())
                 }
+                // Otherwise, not connected or different connection info.
+                _ => self
+                    .join_round(conn_info)
+                    .err()
+                    .map(|e| GameWsResponse::FailedToConnect(e.to_string())),
+            },
+            #[cfg(any(test, feature = "debug-console"))]
+            GameWsRequest::UseTransport(transport) => {
+                debug!("Using transport: {:?}", transport);
+                self.transport = transport;
+                if !matches!(self.ws, WebSocketConnection::None) {
+                    self.close(CloseCause::Client(wire::Close::default()));
+                }
+                None
             }
-            GameWsRequest::CloseSocket => {
-                self.link.send_message(YewWebSocketStatus::Closed);
-            }
-            GameWsRequest::Send(data) => {
-                if let WebSocketConnection::Connected { task, .. } = &mut self.ws {
-                    for sub in self.sent_subscribers.iter() {
-                        self.link.respond(*sub, GameWsResponse::Sent(data.clone()));
+            GameWsRequest::Close(close) => {
+                match self.ws {
+                    WebSocketConnection::None => {}
+                    WebSocketConnection::Pending { .. } => self.close(CloseCause::Client(close)),
+                    WebSocketConnection::Connected { .. } => {
+                        self.send(WsRequest(close.clone().into()));
+                        self.close(CloseCause::Client(close));
                     }
-                    task.send(Json(&data));
-                } else {
-                    error!("Tried to send on non-opened WebSocket. Ignoring.");
                 }
+                None
             }
-            GameWsRequest::GetWebSocketStatus => {
-                self.link
-                    .respond(sender, WebSocketStatus::from(&self.ws).into());
-            }
-            GameWsRequest::SubscribeToSentMessages(subscribe) => {
-                let sender_position = self.sent_subscribers.iter().position(|id| *id == sender);
-                if subscribe {
-                    if sender_position.is_none() {
-                        trace!("New WS sent subscriber: {:?}", sender);
-                        self.sent_subscribers.push(sender);
-                    }
+            GameWsRequest::Send(data) => {
+                if let WebSocketConnection::Connected { .. } = self.ws {
+                    self.send(data);
                 } else {
-                    if let Some(pos) = sender_position {
-                        self.sent_subscribers.swap_remove(pos);
-                        trace!("WS sent subscriber disconnected: {:?}", sender);
-                    } else {
-                        warn!("Disconnection but no associated subscriber.");
-                    }
+                    error!("Tried to send on non-opened WebSocket. Ignoring.");
                 }
+                None
             }
+            GameWsRequest::GetWebSocketStatus => Some(WebSocketStatus::from(&self.ws).into()),
+            #[cfg(feature = "debug-console")]
+            GameWsRequest::GetStats => Some(GameWsResponse::Stats(self.stats.clone())),
+            // Handled by the agent, which knows the subscribers.
+            GameWsRequest::SubscribeToSentMessages(_) => None,
         }
     }
 
-    fn connected(&mut self, id: HandlerId) {
-        trace!("New connection: {:?}", id);
-        if !self.subscribers.contains(&id) {
-            self.subscribers.push(id);
+    /// Tells the server that the client is leaving, when the agent is destroyed.
+    fn leave(&mut self) {
+        if let WebSocketConnection::Connected { .. } = self.ws {
+            self.send(WsRequest(
+                wire::Close::new(wire::CloseReason::Leaving).into(),
+            ));
         }
     }
 
-    fn disconnected(&mut self, id: HandlerId) {
-        // Remove the subscriber (efficiently, hence swap_remove)
-        if let Some(pos) = self.subscribers.iter().position(|x| *x == id) {
-            self.subscribers.swap_remove(pos);
-            trace!("Subscriber disconnected: {:?}", id);
-        } else {
-            warn!("Disconnection but no associated subscriber.");
+    fn take_outputs(&mut self) -> Vec<GameWsResponse> {
+        std::mem::take(&mut self.outputs)
+    }
+
+    fn send(&mut self, data: WsRequest) {
+        if let WebSocketConnection::Connected {
+            connection,
+            encoding,
+            ..
+        } = &mut self.ws
+        {
+            self.outputs.push(GameWsResponse::Sent(data.clone()));
+            match encode_frame(*encoding, &data.0) {
+                Ok(frame) => {
+                    #[cfg(feature = "debug-console")]
+                    self.stats.sent(data.0.type_tag(), frame.size());
+                    connection.send(frame);
+                }
+                Err(e) => error!("Failed to encode {:?}: {}", data, e),
+            }
         }
     }
+
+    /// Closes the connection for good: dropping it closes the underlying
+    /// WebSocket, whose notifications are no longer received.
+    fn close(&mut self, cause: CloseCause) {
+        self.ws = WebSocketConnection::None;
+        self.status_changed();
+        self.outputs.push(GameWsResponse::Closed(cause));
+    }
+
+    fn status_changed(&mut self) {
+        #[cfg(feature = "debug-console")]
+        self.stats
+            .status_changed(&WebSocketStatus::from(&self.ws), now());
+    }
+
+    fn join_round(&mut self, info: GameWsConnectionInfo) -> Result<()> {
+        // The current connection, to another game, is replaced.
+        self.leave();
+
+        let url = ApiUrl::round_socket(&info, wire::Encoding::PREFERRED).to_string();
+        let connection =
+            self.transport
+                .connect(&url, self.on_frame.clone(), self.on_status.clone())?;
+        self.ws = WebSocketConnection::Pending {
+            info: info.clone(),
+            connection,
+        };
+        self.status_changed();
+        self.outputs.push(GameWsResponse::Connecting(info));
+        Ok(())
+    }
 }
 
-impl GameWsMgr {
-    fn broadcast_to_subscribers(&self, output: impl Into<GameWsResponse>) {
-        let output = output.into();
-        for sub in self.subscribers.iter() {
-            self.link.respond(*sub, output.clone());
-        }
+fn encode_frame(encoding: wire::Encoding, message: &wire::Message) -> Result<Frame> {
+    let bytes = encoding.encode(message)?;
+    if encoding.is_binary() {
+        Ok(Frame::Binary(bytes))
+    } else {
+        Ok(Frame::Text(String::from_utf8(bytes)?))
     }
+}
 
-    fn join_round(&mut self, game_id: String, player_id: String) -> Result<()> {
-        let url = format!("/api/round/{}/join?playerId={}", &game_id, &player_id);
+/// The current time, in milliseconds since the Unix epoch, to time the
+/// statuses of the connection. The tests run outside of a browser, without it.
+#[cfg(all(feature = "debug-console", not(test)))]
+fn now() -> f64 {
+    js_sys::Date::now()
+}
 
-        // Build the URL with the right base, i.e. the same as the site
-        let base = web_sys::window().unwrap().location().href().unwrap();
-        let url = web_sys::Url::new_with_base(&url, &base)
-            .map_err(|js_err| anyhow!("{:?}", js_err))
-            .context("Failed to build WebSocket address")?;
-        url.set_protocol("ws");
-        let url = url.href();
+#[cfg(all(feature = "debug-console", test))]
+fn now() -> f64 {
+    0.0
+}
 
-        debug!("Connecting to WebSocket using URL: {}", &url);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
 
-        let callback = self.link.callback(|Json(data)| Msg::WsReceived(data));
-        let notification = self.link.callback(Msg::WsNotification);
+    /// A socket talking to a loopback, whose notifications are queued like the
+    /// agent link does, and delivered by `outputs`.
+    struct Harness {
+        socket: GameSocket,
+        loopback: Loopback,
+        queue: Rc<RefCell<Vec<Msg>>>,
+    }
 
-        match self.ws_service.connect(&url, callback, notification) {
-            Ok(task) => {
-                let info = GameWsConnectionInfo { game_id, player_id };
-                self.ws = WebSocketConnection::Pending {
-                    info: info.clone(),
-                    task,
-                };
-                self.broadcast_to_subscribers(GameWsResponse::Connecting(info));
-                Ok(())
+    impl Harness {
+        fn new(loopback: Loopback) -> Self {
+            let queue = Rc::new(RefCell::new(Vec::new()));
+            let frames = queue.clone();
+            let statuses = queue.clone();
+            let socket = GameSocket::new(
+                Box::new(loopback.clone()),
+                Callback::from(move |frame: Result<Frame>| {
+                    frames.borrow_mut().push(Msg::WsReceived(frame.into()))
+                }),
+                Callback::from(move |status| statuses.borrow_mut().push(Msg::from(status))),
+            );
+            Harness {
+                socket,
+                loopback,
+                queue,
+            }
+        }
+
+        fn request(&mut self, request: GameWsRequest) -> Option<GameWsResponse> {
+            self.socket.handle_input(request)
+        }
+
+        /// Delivers the queued notifications, and returns what the
+        /// subscribers were told since the last call.
+        fn outputs(&mut self) -> Vec<GameWsResponse> {
+            loop {
+                let msgs = std::mem::take(&mut *self.queue.borrow_mut());
+                if msgs.is_empty() {
+                    return self.socket.take_outputs();
+                }
+                for msg in msgs {
+                    self.socket.update(msg);
+                }
+            }
+        }
+
+        fn join(&mut self, game_id: &str) {
+            assert!(self
+                .request(GameWsRequest::JoinRound(info(game_id)))
+                .is_none());
+            assert!(matches!(
+                self.outputs().as_slice(),
+                [GameWsResponse::Connecting(_), GameWsResponse::Connected(_)]
+            ));
+        }
+
+        #[cfg(feature = "debug-console")]
+        fn stats(&mut self) -> TrafficStats {
+            match self.request(GameWsRequest::GetStats) {
+                Some(GameWsResponse::Stats(stats)) => stats,
+                other => panic!("unexpected response: {:?}", other),
             }
-            Err(e) => anyhow::bail!("Failed to connect WebSocket: {}", e),
         }
     }
+
+    fn info(game_id: &str) -> GameWsConnectionInfo {
+        GameWsConnectionInfo {
+            game_id: game_id.to_string(),
+            player_id: "p1".to_string(),
+            spectator: None,
+        }
+    }
+
+    fn sent_messages(loopback: &Loopback) -> Vec<wire::Message> {
+        loopback
+            .take_sent_messages()
+            .into_iter()
+            .map(|message| message.expect("sent an invalid message"))
+            .collect()
+    }
+
+    #[test]
+    fn joins_and_exchanges_messages() {
+        let mut harness = Harness::new(Loopback::new());
+        harness.join("g1");
+        assert_eq!(
+            harness.loopback.url(),
+            Some(ApiUrl::round_socket(&info("g1"), wire::Encoding::PREFERRED).to_string())
+        );
+
+        harness.request(GameWsRequest::Send(WsRequest(wire::Message::Ping)));
+        assert!(matches!(
+            harness.outputs().as_slice(),
+            [GameWsResponse::Sent(WsRequest(wire::Message::Ping))]
+        ));
+        assert_eq!(sent_messages(&harness.loopback), vec![wire::Message::Ping]);
+
+        harness.loopback.push(&wire::Message::Pong).unwrap();
+        harness
+            .loopback
+            .push_frame(Frame::Text("not JSON".to_string()))
+            .unwrap();
+        assert!(matches!(
+            harness.outputs().as_slice(),
+            [
+                GameWsResponse::Received(WsResponse(wire::Message::Pong)),
+                GameWsResponse::ReceivedError(_),
+            ]
+        ));
+
+        // Joining the same game again keeps the connection.
+        assert_eq!(
+            harness
+                .request(GameWsRequest::JoinRound(info("g1")))
+                .map(|response| format!("{:?}", response)),
+            Some(format!(
+                "{:?}",
+                GameWsResponse::WebSocketStatus(WebSocketStatus::Connected(info("g1")))
+            ))
+        );
+        assert!(harness.outputs().is_empty());
+
+        #[cfg(feature = "debug-console")]
+        {
+            let stats = harness.stats();
+            assert_eq!(stats.sent["PING"].messages, 1);
+            assert_eq!(stats.received["PONG"].messages, 1);
+            assert_eq!(stats.decode_failures.messages, 1);
+        }
+    }
+
+    #[test]
+    fn joining_another_game_leaves_the_current_one() {
+        let mut harness = Harness::new(Loopback::new());
+        harness.join("g1");
+        assert!(harness
+            .request(GameWsRequest::JoinRound(info("g2")))
+            .is_none());
+
+        assert!(matches!(
+            harness.outputs().as_slice(),
+            [
+                GameWsResponse::Sent(_),
+                GameWsResponse::Connecting(_),
+                GameWsResponse::Connected(_),
+            ]
+        ));
+        assert_eq!(
+            sent_messages(&harness.loopback),
+            vec![wire::Close::new(wire::CloseReason::Leaving).into()]
+        );
+        assert_eq!(
+            harness.loopback.url(),
+            Some(ApiUrl::round_socket(&info("g2"), wire::Encoding::PREFERRED).to_string())
+        );
+    }
+
+    #[test]
+    fn closes_with_a_message() {
+        let mut harness = Harness::new(Loopback::new());
+        harness.join("g1");
+
+        let close = wire::Close::new(wire::CloseReason::Leaving);
+        harness.request(GameWsRequest::Close(close.clone()));
+        let outputs = harness.outputs();
+        assert!(matches!(
+            outputs.as_slice(),
+            [GameWsResponse::Sent(_), GameWsResponse::Closed(CloseCause::Client(c))] if *c == close
+        ));
+        assert_eq!(sent_messages(&harness.loopback), vec![close.into()]);
+        assert!(!harness.loopback.is_connected());
+
+        // Nothing to close anymore.
+        harness.request(GameWsRequest::Close(wire::Close::default()));
+        assert!(harness.outputs().is_empty());
+    }
+
+    #[test]
+    fn closes_a_pending_connection() {
+        let mut harness = Harness::new(Loopback::with_manual_open());
+        harness.request(GameWsRequest::JoinRound(info("g1")));
+        assert!(matches!(
+            harness.outputs().as_slice(),
+            [GameWsResponse::Connecting(_)]
+        ));
+
+        harness.request(GameWsRequest::Send(WsRequest(wire::Message::Ping)));
+        harness.request(GameWsRequest::Close(wire::Close::default()));
+        assert!(matches!(
+            harness.outputs().as_slice(),
+            [GameWsResponse::Closed(CloseCause::Client(_))]
+        ));
+        assert!(harness.loopback.take_sent_frames().is_empty());
+        assert!(!harness.loopback.is_connected());
+    }
+
+    #[test]
+    fn honors_the_close_of_the_server() {
+        let mut harness = Harness::new(Loopback::new());
+        harness.join("g1");
+
+        let close = wire::Close::new(wire::CloseReason::Kicked);
+        harness.loopback.push(&close.clone().into()).unwrap();
+        let outputs = harness.outputs();
+        assert!(matches!(
+            outputs.as_slice(),
+            [
+                GameWsResponse::Received(_),
+                GameWsResponse::Closed(cause @ CloseCause::Server(_)),
+            ] if !cause.should_reconnect()
+        ));
+        assert!(!harness.loopback.is_connected());
+    }
+
+    #[test]
+    fn reconnects_after_losing_the_connection() {
+        let mut harness = Harness::new(Loopback::new());
+        harness.join("g1");
+
+        harness.loopback.close();
+        let outputs = harness.outputs();
+        assert!(matches!(
+            outputs.as_slice(),
+            [GameWsResponse::Closed(cause @ CloseCause::Lost)] if cause.should_reconnect()
+        ));
+        assert_eq!(
+            WebSocketStatus::from(&harness.socket.ws),
+            WebSocketStatus::NotConnected
+        );
+
+        harness.join("g1");
+        harness.loopback.fail();
+        assert!(matches!(
+            harness.outputs().as_slice(),
+            [GameWsResponse::ErrorOccurred]
+        ));
+
+        harness.join("g1");
+        #[cfg(feature = "debug-console")]
+        assert_eq!(harness.stats().reconnects, 2);
+    }
+
+    #[test]
+    fn replaces_the_transport() {
+        let mut harness = Harness::new(Loopback::new());
+        harness.join("g1");
+
+        let other = Loopback::new();
+        harness.request(GameWsRequest::UseTransport(Box::new(other.clone())));
+        assert!(matches!(
+            harness.outputs().as_slice(),
+            [GameWsResponse::Closed(CloseCause::Client(_))]
+        ));
+        assert!(!harness.loopback.is_connected());
+
+        harness.join("g1");
+        assert!(other.is_connected());
+        assert!(!harness.loopback.is_connected());
+    }
 }
@@ -1,4 +1,7 @@
 pub mod game_mgr;
 pub mod game_ws_mgr;
 pub mod notifications;
+pub mod settings_bus;
 pub mod state_mgr;
+#[cfg(feature = "debug-console")]
+pub mod ws_stats;
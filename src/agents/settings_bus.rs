@@ -0,0 +1,46 @@
+use log::*;
+use yew::worker::*;
+
+use crate::settings::Settings;
+
+/// Tells the components showing them that the settings were changed, e.g. by
+/// the settings page.
+pub struct SettingsBus {
+    link: AgentLink<Self>,
+    subscribers: Vec<HandlerId>,
+}
+
+impl Agent for SettingsBus {
+    type Reach = Context<Self>;
+    type Message = ();
+    type Input = Settings;
+    type Output = Settings;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        Self {
+            link,
+            subscribers: Vec::with_capacity(10),
+        }
+    }
+
+    fn update(&mut self, _: Self::Message) {}
+
+    fn handle_input(&mut self, settings: Self::Input, sender: HandlerId) {
+        trace!("Settings changed by '{:?}'", sender);
+        for sub in self.subscribers.iter() {
+            self.link.respond(*sub, settings.clone());
+        }
+    }
+
+    fn connected(&mut self, id: HandlerId) {
+        if !self.subscribers.contains(&id) {
+            self.subscribers.push(id);
+        }
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        if let Some(pos) = self.subscribers.iter().position(|x| *x == id) {
+            self.subscribers.swap_remove(pos);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Statistics on the traffic of the connection to the game server, to see
+//! which messages take the bandwidth and spot misbehaving servers.
+
+use std::collections::BTreeMap;
+
+use super::game_ws_mgr::{GameWsConnectionInfo, WebSocketStatus};
+
+/// The kind of a `WebSocketStatus`, without the connection info.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StatusKind {
+    NotConnected,
+    Pending,
+    Connected,
+}
+
+impl StatusKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusKind::NotConnected => "Not connected",
+            StatusKind::Pending => "Connecting",
+            StatusKind::Connected => "Connected",
+        }
+    }
+}
+
+impl From<&WebSocketStatus> for StatusKind {
+    fn from(status: &WebSocketStatus) -> Self {
+        match status {
+            WebSocketStatus::NotConnected => StatusKind::NotConnected,
+            WebSocketStatus::Pending(_) => StatusKind::Pending,
+            WebSocketStatus::Connected(_) => StatusKind::Connected,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counter {
+    pub messages: u64,
+    pub bytes: u64,
+}
+
+impl Counter {
+    fn add(&mut self, bytes: usize) {
+        self.messages += 1;
+        self.bytes += bytes as u64;
+    }
+}
+
+/// The traffic of the connections to one game as one player. Connecting to
+/// another game, or as another player, starts new statistics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrafficStats {
+    pub info: Option<GameWsConnectionInfo>,
+    /// The sent messages, by type.
+    pub sent: BTreeMap<String, Counter>,
+    /// The received messages, by type.
+    pub received: BTreeMap<String, Counter>,
+    /// The received frames which could not be decoded.
+    pub decode_failures: Counter,
+    /// How many times the connection was opened again after the first time.
+    pub reconnects: u64,
+    /// Milliseconds spent in each status before the current one.
+    durations: BTreeMap<StatusKind, f64>,
+    status: StatusKind,
+    /// When the current status started, in milliseconds since the Unix epoch.
+    status_since: f64,
+}
+
+impl TrafficStats {
+    pub fn new(now: f64) -> Self {
+        TrafficStats {
+            info: None,
+            sent: BTreeMap::new(),
+            received: BTreeMap::new(),
+            decode_failures: Counter::default(),
+            reconnects: 0,
+            durations: BTreeMap::new(),
+            status: StatusKind::NotConnected,
+            status_since: now,
+        }
+    }
+
+    pub fn sent(&mut self, r#type: &str, bytes: usize) {
+        count(&mut self.sent, r#type, bytes);
+    }
+
+    pub fn received(&mut self, r#type: &str, bytes: usize) {
+        count(&mut self.received, r#type, bytes);
+    }
+
+    pub fn decode_failed(&mut self, bytes: usize) {
+        self.decode_failures.add(bytes);
+    }
+
+    /// Records a change of the status of the connection.
+    pub fn status_changed(&mut self, status: &WebSocketStatus, now: f64) {
+        if let WebSocketStatus::Pending(info) = status {
+            if self.info.as_ref() == Some(info) {
+                self.reconnects += 1;
+            } else {
+                *self = TrafficStats::new(now);
+                self.info = Some(info.clone());
+            }
+        }
+
+        let kind = StatusKind::from(status);
+        if kind != self.status {
+            *self.durations.entry(self.status).or_default() += now - self.status_since;
+            self.status = kind;
+            self.status_since = now;
+        }
+    }
+}
+
+/// Read by the debug tools only.
+impl TrafficStats {
+    /// The milliseconds spent in each status, including the current one.
+    pub fn time_in_status(&self, now: f64) -> BTreeMap<StatusKind, f64> {
+        let mut durations = self.durations.clone();
+        *durations.entry(self.status).or_default() += now - self.status_since;
+        durations
+    }
+
+    pub fn total_sent(&self) -> Counter {
+        total(&self.sent)
+    }
+
+    pub fn total_received(&self) -> Counter {
+        total(&self.received)
+    }
+}
+
+/// Counts a message, only allocating its type the first time it is met.
+fn count(counters: &mut BTreeMap<String, Counter>, r#type: &str, bytes: usize) {
+    match counters.get_mut(r#type) {
+        Some(counter) => counter.add(bytes),
+        None => counters.entry(r#type.to_string()).or_default().add(bytes),
+    }
+}
+
+fn total(counters: &BTreeMap<String, Counter>) -> Counter {
+    counters
+        .values()
+        .fold(Counter::default(), |total, counter| Counter {
+            messages: total.messages + counter.messages,
+            bytes: total.bytes + counter.bytes,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(game_id: &str) -> GameWsConnectionInfo {
+        GameWsConnectionInfo {
+            game_id: game_id.to_string(),
+            player_id: "p1".to_string(),
+            spectator: None,
+        }
+    }
+
+    #[test]
+    fn counts_messages_by_type() {
+        let mut stats = TrafficStats::new(0.0);
+        stats.sent("PING", 15);
+        stats.sent("PING", 15);
+        stats.received("PONG", 15);
+        stats.received("GAME_STARTED", 23);
+        stats.decode_failed(4);
+
+        assert_eq!(
+            stats.sent["PING"],
+            Counter {
+                messages: 2,
+                bytes: 30
+            }
+        );
+        assert_eq!(
+            stats.total_received(),
+            Counter {
+                messages: 2,
+                bytes: 38
+            }
+        );
+        assert_eq!(stats.decode_failures.messages, 1);
+    }
+
+    #[test]
+    fn tracks_connections() {
+        let mut stats = TrafficStats::new(0.0);
+        stats.status_changed(&WebSocketStatus::Pending(info("g1")), 100.0);
+        stats.status_changed(&WebSocketStatus::Connected(info("g1")), 150.0);
+        stats.received("PONG", 15);
+        stats.status_changed(&WebSocketStatus::NotConnected, 1150.0);
+        stats.status_changed(&WebSocketStatus::Pending(info("g1")), 1200.0);
+
+        assert_eq!(stats.reconnects, 1);
+        assert_eq!(stats.received.len(), 1);
+        let durations = stats.time_in_status(1250.0);
+        assert_eq!(durations[&StatusKind::NotConnected], 50.0);
+        assert_eq!(durations[&StatusKind::Pending], 100.0);
+        assert_eq!(durations[&StatusKind::Connected], 1000.0);
+
+        // Another game starts over.
+        stats.status_changed(&WebSocketStatus::Pending(info("g2")), 1300.0);
+        assert_eq!(stats.info, Some(info("g2")));
+        assert_eq!(stats.reconnects, 0);
+        assert!(stats.received.is_empty());
+        assert_eq!(stats.time_in_status(1400.0)[&StatusKind::Pending], 100.0);
+    }
+}
@@ -9,6 +9,7 @@ use yew_router::prelude::*;
 use crate::components;
 use crate::pages;
 use crate::routes::*;
+use crate::services::api::decode;
 use crate::state::State;
 
 const KEY: &str = "cards-client-rs.state";
@@ -97,9 +98,13 @@ impl Component for App {
                                         AppRoute::ListGames => html!{ <pages::ListGames /> },
                                         AppRoute::CreateGame => html!{ <pages::CreateGame /> },
                                         AppRoute::JoinGame { game_id, username } =>
-                                            html!{ <pages::JoiningGame game_id=game_id username=username /> },
+                                            html!{ <pages::JoiningGame game_id=decode(game_id) username=decode(username) /> },
                                         AppRoute::PlayGame { game_id, player_id } =>
-                                            html!{ <pages::PlayGame game_id=game_id player_id=player_id /> },
+                                            html!{ <pages::PlayGame game_id=decode(game_id) player_id=decode(player_id) /> },
+                                        AppRoute::WatchGame { game_id, username } =>
+                                            html!{ <pages::PlayGame game_id=decode(game_id) player_id=decode(username) spectator=true /> },
+                                        AppRoute::Replay => html!{ <pages::Replay /> },
+                                        AppRoute::Settings => html!{ <pages::SettingsPage /> },
                                         AppRoute::NotFound(route) =>
                                             html!{ <pages::NotFound route=route /> },
                                     };
@@ -122,14 +127,18 @@ impl Component for App {
                     </div>
                 </footer>
 
-                <div class="section site-content">
-                    <div class="container">
-                        <div class="box">
-                            <components::WebSocketDebugConsole />
-                        </div>
-                    </div>
-                </div>
+                { debug_console() }
             </>
         }
     }
 }
+
+#[cfg(feature = "debug-console")]
+fn debug_console() -> Html {
+    html! { <components::DebugDrawer /> }
+}
+
+#[cfg(not(feature = "debug-console"))]
+fn debug_console() -> Html {
+    html! {}
+}
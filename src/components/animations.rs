@@ -0,0 +1,241 @@
+//! Animations of the cards on the table.
+//!
+//! The table is rendered from the game state only, so the cards jump to their
+//! new place whenever the state changes. To animate them, the table measures
+//! where its cards are before rendering the new state, and once rendered moves
+//! each card back to its old place with a transform, which is then removed
+//! through a transition. Cards which didn't exist come from the middle of the
+//! table, one after the other, like when dealing, and flipped cards turn over.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement};
+
+use crate::components::table::COMPONENT_ID_ATTRIBUTE;
+use crate::wire::ComponentId;
+
+const MOVE_DURATION_MS: f64 = 300.0;
+const FLIP_DURATION_MS: f64 = 300.0;
+const DEAL_INTERVAL_MS: f64 = 80.0;
+/// Long deals are sped up so that they never take longer than this.
+const MAX_DEAL_DELAY_MS: f64 = 1200.0;
+
+/// Positions of the top left corners of the components, in pixels.
+pub type Positions = HashMap<ComponentId, (f64, f64)>;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AnimationSpeed {
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl AnimationSpeed {
+    pub const ALL: [AnimationSpeed; 3] = [
+        AnimationSpeed::Slow,
+        AnimationSpeed::Normal,
+        AnimationSpeed::Fast,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AnimationSpeed::Slow => "Slow",
+            AnimationSpeed::Normal => "Normal",
+            AnimationSpeed::Fast => "Fast",
+        }
+    }
+
+    fn factor(self) -> f64 {
+        match self {
+            AnimationSpeed::Slow => 2.0,
+            AnimationSpeed::Normal => 1.0,
+            AnimationSpeed::Fast => 0.5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Animations {
+    pub speed: AnimationSpeed,
+    /// Turns all the animations off.
+    pub reduced_motion: bool,
+}
+
+impl Default for Animations {
+    fn default() -> Self {
+        Animations {
+            speed: AnimationSpeed::Normal,
+            reduced_motion: false,
+        }
+    }
+}
+
+impl Animations {
+    /// The default settings, following the preference of the browser about
+    /// reduced motion.
+    pub fn from_browser() -> Self {
+        let reduced_motion = web_sys::window()
+            .and_then(|window| {
+                window
+                    .match_media("(prefers-reduced-motion: reduce)")
+                    .ok()
+                    .flatten()
+            })
+            .map_or(false, |query| query.matches());
+        Animations {
+            reduced_motion,
+            ..Animations::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.reduced_motion
+    }
+
+    fn duration(&self, base_ms: f64) -> f64 {
+        base_ms * self.speed.factor()
+    }
+
+    /// Delay before the card dealt at the given index starts moving.
+    fn deal_delay(&self, index: usize, count: usize) -> f64 {
+        let interval = match count {
+            0 | 1 => 0.0,
+            count => DEAL_INTERVAL_MS.min(MAX_DEAL_DELAY_MS / (count - 1) as f64),
+        };
+        self.duration(interval * index as f64)
+    }
+}
+
+/// Measures the positions of the components rendered under `root`.
+pub fn measure(root: &Element) -> Positions {
+    components(root)
+        .map(|(id, element)| {
+            let rect = element.get_bounding_client_rect();
+            (id, (rect.left(), rect.top()))
+        })
+        .collect()
+}
+
+/// Animates the given cards from the positions they had before the last
+/// render, and turns over the flipped ones.
+pub fn play(
+    root: &Element,
+    before: &Positions,
+    cards: &HashSet<ComponentId>,
+    flipped: &HashSet<ComponentId>,
+    settings: &Animations,
+) {
+    if !settings.is_enabled() {
+        return;
+    }
+
+    let table = root.get_bounding_client_rect();
+    let table_center = (
+        table.left() + table.width() / 2.0,
+        table.top() + table.height() / 2.0,
+    );
+
+    let mut moves = Vec::new();
+    let mut deals = Vec::new();
+    let mut flips = Vec::new();
+    for (id, element) in components(root).filter(|(id, _)| cards.contains(id)) {
+        let rect = element.get_bounding_client_rect();
+        match before.get(&id) {
+            Some((left, top)) => {
+                let offset = (left - rect.left(), top - rect.top());
+                if offset.0.abs() > 0.5 || offset.1.abs() > 0.5 {
+                    moves.push((element.clone(), offset, 0.0));
+                }
+            }
+            None => {
+                let offset = (
+                    table_center.0 - rect.left() - rect.width() / 2.0,
+                    table_center.1 - rect.top() - rect.height() / 2.0,
+                );
+                deals.push((element.clone(), offset));
+            }
+        }
+        if flipped.contains(&id) {
+            if let Some(card) = element
+                .first_element_child()
+                .and_then(|card| card.dyn_into::<HtmlElement>().ok())
+            {
+                flips.push(card);
+            }
+        }
+    }
+    let dealt = deals.len();
+    moves.extend(
+        deals
+            .into_iter()
+            .enumerate()
+            .map(|(index, (element, offset))| (element, offset, settings.deal_delay(index, dealt))),
+    );
+
+    // Puts the cards back where they were, without transition.
+    for (element, (x, y), _) in moves.iter() {
+        let style = element.style();
+        let _ = style.set_property("transition", "none");
+        let _ = style.set_property("transform", &format!("translate({}px, {}px)", x, y));
+    }
+    for card in flips.iter() {
+        let _ = card.style().set_property("animation", "none");
+    }
+
+    // Measuring forces the browser to apply the styles above, otherwise it
+    // would only see the ones below and nothing would move.
+    let _ = root.get_bounding_client_rect();
+
+    let move_duration = settings.duration(MOVE_DURATION_MS);
+    for (element, _, delay) in moves.iter() {
+        let style = element.style();
+        let _ = style.set_property(
+            "transition",
+            &format!("transform {}ms ease-in-out {}ms", move_duration, delay),
+        );
+        let _ = style.remove_property("transform");
+    }
+    let flip_duration = settings.duration(FLIP_DURATION_MS);
+    for card in flips.iter() {
+        let _ = card.style().set_property(
+            "animation",
+            &format!("game-card-flip {}ms ease-out", flip_duration),
+        );
+    }
+}
+
+/// Returns the rendered components under `root`, with their IDs.
+fn components(root: &Element) -> impl Iterator<Item = (ComponentId, HtmlElement)> {
+    let selector = format!("[{}]", COMPONENT_ID_ATTRIBUTE);
+    let nodes = root.query_selector_all(&selector).ok();
+    let count = nodes.as_ref().map_or(0, |nodes| nodes.length());
+    (0..count).filter_map(move |index| {
+        let element = nodes
+            .as_ref()?
+            .item(index)?
+            .dyn_into::<HtmlElement>()
+            .ok()?;
+        let id = element.get_attribute(COMPONENT_ID_ATTRIBUTE)?;
+        Some((id.into(), element))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deals_are_staggered() {
+        let settings = Animations::default();
+        assert_eq!(settings.deal_delay(0, 1), 0.0);
+        assert_eq!(settings.deal_delay(3, 5), 3.0 * DEAL_INTERVAL_MS);
+        assert!((settings.deal_delay(99, 100) - MAX_DEAL_DELAY_MS).abs() < 1e-6);
+
+        let slow = Animations {
+            speed: AnimationSpeed::Slow,
+            ..Animations::default()
+        };
+        assert_eq!(slow.deal_delay(3, 5), 6.0 * DEAL_INTERVAL_MS);
+    }
+}
@@ -0,0 +1,72 @@
+use yew::prelude::*;
+
+use crate::agents::game_mgr::{GameMgr, GameMgrResponse};
+use crate::game::aria::Announcement;
+
+/// Number of messages kept in the live region. It is emptied once full rather
+/// than shifted, as screen readers would read out the shifted messages again.
+const MAX_MESSAGES: usize = 20;
+
+/// Reads out the announcements of the game manager to the screen readers,
+/// without displaying them.
+pub struct Announcer {
+    _game_mgr_agent: Box<dyn Bridge<GameMgr>>,
+
+    messages: Vec<String>,
+    alert: Option<String>,
+}
+
+pub enum Msg {
+    GameMgrMessage(GameMgrResponse),
+}
+
+impl Component for Announcer {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Announcer {
+            _game_mgr_agent: GameMgr::bridge(link.callback(Msg::GameMgrMessage)),
+
+            messages: Vec::new(),
+            alert: None,
+        }
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::GameMgrMessage(GameMgrResponse::Announcement(Announcement {
+                message,
+                assertive: true,
+            })) => {
+                self.alert = Some(message);
+                true
+            }
+            Msg::GameMgrMessage(GameMgrResponse::Announcement(announcement)) => {
+                if self.messages.len() >= MAX_MESSAGES {
+                    self.messages.clear();
+                }
+                self.messages.push(announcement.message);
+                true
+            }
+            Msg::GameMgrMessage(_) => false,
+        }
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="is-sr-only">
+                <div role="log" aria-live="polite">
+                    { for self.messages.iter().map(|message| html! { <p>{ message }</p> }) }
+                </div>
+                <div role="alert">
+                    { for self.alert.iter().map(|message| html! { <p>{ message }</p> }) }
+                </div>
+            </div>
+        }
+    }
+}
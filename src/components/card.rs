@@ -0,0 +1,142 @@
+use yew::prelude::*;
+use yewtil::{Pure, PureComponent};
+
+use crate::components::card_faces::{self, BackTheme};
+use crate::game::faces::Face;
+use crate::wire;
+
+/// Name of the property displayed as the cost of a card, when it is a number.
+const COST_PROPERTY: &str = "cost";
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct PureCardView {
+    pub card: wire::Card,
+    /// Whether the face of the card is visible to the local player.
+    #[prop_or(true)]
+    pub face_up: bool,
+    /// Look of the back of the card, when the server doesn't send an image.
+    #[prop_or_default]
+    pub back_theme: BackTheme,
+}
+pub type CardView = Pure<PureCardView>;
+
+impl PureComponent for PureCardView {
+    fn render(&self) -> Html {
+        let card = &self.card;
+        let state = &card.state;
+
+        if !self.face_up {
+            return render_back(card, &self.back_theme);
+        }
+
+        // The name and the rank of the card are drawn on the generated faces.
+        let face = match &card.front_image {
+            Some(_) => None,
+            None => Face::from_state(state),
+        };
+        let image = match (&card.front_image, &face) {
+            (Some(url), _) => html! { <img class="game-card-image" src=url alt=&card.name /> },
+            (None, Some(face)) => html! {
+                <img
+                    class="game-card-image"
+                    src=card_faces::data_url(&face.to_svg())
+                    alt=face.label()
+                    />
+            },
+            (None, None) => html! {},
+        };
+        let name = match &face {
+            Some(_) => html! {},
+            None => html! { <p class="game-card-name">{ &card.name }</p> },
+        };
+
+        let cost = match state.properties.get(COST_PROPERTY) {
+            Some(wire::CardProperty::Integer(cost)) => {
+                html! { <span class="game-card-cost">{ cost }</span> }
+            }
+            Some(wire::CardProperty::Float(cost)) => {
+                html! { <span class="game-card-cost">{ cost }</span> }
+            }
+            _ => html! {},
+        };
+
+        let rank = match (&state.value, &state.suit) {
+            _ if face.is_some() => html! {},
+            (None, None) => html! {},
+            (value, suit) => html! {
+                <p class="game-card-rank">
+                    { value.as_deref().unwrap_or_default() }
+                    { suit.as_deref().unwrap_or_default() }
+                </p>
+            },
+        };
+
+        html! {
+            <div class=("game-card", face.as_ref().map(|_| "game-card-generated")) title=&card.description>
+                { cost }
+                { image }
+                { name }
+                { rank }
+                <div class="field is-grouped is-grouped-multiline">
+                    {
+                        for state
+                            .properties
+                            .iter()
+                            .filter(|(name, property)| !is_cost(name, property))
+                            .map(|(name, property)| render_property(name, property))
+                    }
+                </div>
+            </div>
+        }
+    }
+}
+
+fn render_back(card: &wire::Card, theme: &BackTheme) -> Html {
+    let url = match &card.back_image {
+        Some(url) => url.clone(),
+        None => card_faces::data_url(&theme.to_svg()),
+    };
+    html! {
+        <div class="game-card game-card-back">
+            <img class="game-card-image" src=url alt="Card back" />
+        </div>
+    }
+}
+
+fn is_cost(name: &str, property: &wire::CardProperty) -> bool {
+    match property {
+        wire::CardProperty::Integer(_) | wire::CardProperty::Float(_) => name == COST_PROPERTY,
+        _ => false,
+    }
+}
+
+fn render_property(name: &str, property: &wire::CardProperty) -> Html {
+    html! {
+        <div class="control">
+            <div class="tags has-addons">
+                <span class="tag is-dark">{ name }</span>
+                { render_property_value(property) }
+            </div>
+        </div>
+    }
+}
+
+fn render_property_value(property: &wire::CardProperty) -> Html {
+    match property {
+        wire::CardProperty::Bool(true) => html! { <span class="tag is-success">{ "yes" }</span> },
+        wire::CardProperty::Bool(false) => html! { <span class="tag is-light">{ "no" }</span> },
+        wire::CardProperty::Integer(number) => {
+            html! { <span class="tag is-warning">{ number }</span> }
+        }
+        wire::CardProperty::Float(number) => {
+            html! { <span class="tag is-warning">{ number }</span> }
+        }
+        wire::CardProperty::Color { color } => html! {
+            <span class="tag" style=format!("background-color: {}", color) title=color></span>
+        },
+        wire::CardProperty::Text(text) => html! { <span class="tag">{ text }</span> },
+        wire::CardProperty::List(items) => html! {
+            <>{ for items.iter().map(render_property_value) }</>
+        },
+    }
+}
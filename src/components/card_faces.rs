@@ -0,0 +1,428 @@
+//! Card faces and backs drawn as SVG documents, for the cards the server sends
+//! without images.
+//!
+//! Everything is generated from the `CardState` of the cards, so no image has
+//! to be shipped with the application. The documents only use a `viewBox`, which
+//! makes them scale to any size and pixel density, and are meant to be used as
+//! the `src` of an `img` through `data_url`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+use crate::game::faces::{Face, Rank, Suit};
+
+const WIDTH: f64 = 250.0;
+const HEIGHT: f64 = 350.0;
+const CENTER_X: f64 = WIDTH / 2.0;
+const CENTER_Y: f64 = HEIGHT / 2.0;
+
+/// Color of the values of the cards which don't say what their color is.
+const DEFAULT_INK: &str = "#363636";
+const RED_INK: &str = "#d32f2f";
+
+impl Suit {
+    fn ink(self) -> &'static str {
+        if self.is_red() {
+            RED_INK
+        } else {
+            DEFAULT_INK
+        }
+    }
+
+    /// Shape of the suit, drawn in a 100x100 box.
+    fn shape(self) -> &'static str {
+        match self {
+            Suit::Spades => {
+                "<path d='M50 0C65 22 100 40 100 62C100 76 90 85 77 85C68 85 60 80 55 74\
+                 C57 86 62 94 70 100L30 100C38 94 43 86 45 74C40 80 32 85 23 85\
+                 C10 85 0 76 0 62C0 40 35 22 50 0Z'/>"
+            }
+            Suit::Hearts => {
+                "<path d='M50 95C20 70 0 50 0 29C0 12 12 2 26 2C37 2 45 9 50 18\
+                 C55 9 63 2 74 2C88 2 100 12 100 29C100 50 80 70 50 95Z'/>"
+            }
+            Suit::Diamonds => "<path d='M50 0L88 50L50 100L12 50Z'/>",
+            Suit::Clubs => {
+                "<circle cx='50' cy='25' r='23'/><circle cx='25' cy='60' r='23'/>\
+                 <circle cx='75' cy='60' r='23'/>\
+                 <path d='M45 55C45 80 40 92 30 100L70 100C60 92 55 80 55 55Z'/>"
+            }
+        }
+    }
+}
+
+impl Face {
+    /// Draws the face as a standalone SVG document.
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+        open_document(&mut svg);
+        write_outline(&mut svg);
+        match self {
+            Face::Standard { rank, suit } => write_standard(&mut svg, *rank, *suit),
+            Face::Joker { red } => write_joker(&mut svg, *red),
+            Face::Colored { value, color } => write_colored(&mut svg, value, color.as_deref()),
+        }
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackPattern {
+    Lattice,
+    Checkers,
+    Stripes,
+}
+
+/// The look of the generated card backs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BackTheme {
+    pub pattern: BackPattern,
+    /// Any CSS color.
+    pub primary: String,
+    pub secondary: String,
+}
+
+impl Default for BackTheme {
+    fn default() -> Self {
+        BackTheme::new(BackPattern::Lattice, "#2160c4", "#5c8fe0")
+    }
+}
+
+impl BackTheme {
+    pub fn new(pattern: BackPattern, primary: &str, secondary: &str) -> Self {
+        BackTheme {
+            pattern,
+            primary: primary.to_string(),
+            secondary: secondary.to_string(),
+        }
+    }
+
+    /// The themes offered to the players.
+    pub fn presets() -> Vec<(&'static str, BackTheme)> {
+        vec![
+            ("Blue", BackTheme::default()),
+            (
+                "Red",
+                BackTheme::new(BackPattern::Lattice, "#b71c1c", "#e05a5a"),
+            ),
+            (
+                "Green",
+                BackTheme::new(BackPattern::Stripes, "#1b5e20", "#43a047"),
+            ),
+            (
+                "Checkers",
+                BackTheme::new(BackPattern::Checkers, "#263238", "#546e7a"),
+            ),
+        ]
+    }
+
+    /// Draws a card back as a standalone SVG document.
+    pub fn to_svg(&self) -> String {
+        let primary = escape(&self.primary);
+        let secondary = escape(&self.secondary);
+
+        let pattern = match self.pattern {
+            BackPattern::Lattice => format!(
+                "<pattern id='p' width='20' height='20' patternUnits='userSpaceOnUse'>\
+                 <rect width='20' height='20' fill='{0}'/>\
+                 <path d='M0 0L20 20M20 0L0 20' stroke='{1}' stroke-width='3'/></pattern>",
+                primary, secondary
+            ),
+            BackPattern::Checkers => format!(
+                "<pattern id='p' width='24' height='24' patternUnits='userSpaceOnUse'>\
+                 <rect width='24' height='24' fill='{0}'/>\
+                 <rect width='12' height='12' fill='{1}'/>\
+                 <rect x='12' y='12' width='12' height='12' fill='{1}'/></pattern>",
+                primary, secondary
+            ),
+            BackPattern::Stripes => format!(
+                "<pattern id='p' width='16' height='16' patternUnits='userSpaceOnUse' \
+                 patternTransform='rotate(45)'>\
+                 <rect width='16' height='16' fill='{0}'/>\
+                 <rect width='8' height='16' fill='{1}'/></pattern>",
+                primary, secondary
+            ),
+        };
+
+        let mut svg = String::new();
+        open_document(&mut svg);
+        write!(svg, "<defs>{}</defs>", pattern).unwrap();
+        write_outline(&mut svg);
+        write!(
+            svg,
+            "<rect x='14' y='14' width='{w}' height='{h}' rx='10' fill='url(#p)'/>\
+             <ellipse cx='{cx}' cy='{cy}' rx='60' ry='90' fill='{p}' stroke='{s}' stroke-width='6'/>",
+            w = WIDTH - 28.0,
+            h = HEIGHT - 28.0,
+            cx = CENTER_X,
+            cy = CENTER_Y,
+            p = primary,
+            s = secondary,
+        )
+        .unwrap();
+        svg.push_str("</svg>");
+        svg
+    }
+}
+
+/// Makes a URL out of an SVG document, to be used as the source of an image.
+pub fn data_url(svg: &str) -> String {
+    let mut url = String::from("data:image/svg+xml,");
+    for byte in svg.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => url.push(byte as char),
+            b'-' | b'_' | b'.' | b'~' | b'\'' | b'(' | b')' | b'=' | b':' | b'/' | b',' | b';' => {
+                url.push(byte as char)
+            }
+            _ => write!(url, "%{:02X}", byte).unwrap(),
+        }
+    }
+    url
+}
+
+fn open_document(svg: &mut String) {
+    write!(
+        svg,
+        "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 {} {}' \
+         shape-rendering='geometricPrecision' text-rendering='geometricPrecision' \
+         font-family='Helvetica, Arial, sans-serif' font-weight='bold' text-anchor='middle'>",
+        WIDTH, HEIGHT
+    )
+    .unwrap();
+}
+
+fn write_outline(svg: &mut String) {
+    write!(
+        svg,
+        "<rect x='1' y='1' width='{}' height='{}' rx='16' fill='white' stroke='#bbb' stroke-width='2'/>",
+        WIDTH - 2.0,
+        HEIGHT - 2.0
+    )
+    .unwrap();
+}
+
+/// Writes the symbol of a suit, to be drawn with `write_pip`.
+fn write_suit_symbol(svg: &mut String, suit: Suit) {
+    write!(
+        svg,
+        "<defs><symbol id='pip' viewBox='0 0 100 100'>{}</symbol></defs>",
+        suit.shape()
+    )
+    .unwrap();
+}
+
+/// Draws a pip centered on the given point, upside down on the lower half of
+/// the card like on printed cards.
+fn write_pip(svg: &mut String, x: f64, y: f64, size: f64) {
+    write!(
+        svg,
+        "<use href='#pip' x='{}' y='{}' width='{s}' height='{s}'",
+        x - size / 2.0,
+        y - size / 2.0,
+        s = size
+    )
+    .unwrap();
+    if y > CENTER_Y {
+        write!(svg, " transform='rotate(180 {} {})'", x, y).unwrap();
+    }
+    svg.push_str("/>");
+}
+
+/// Draws the same content in the top left corner and, upside down, in the
+/// bottom right one.
+fn write_corners(svg: &mut String, content: &str) {
+    write!(
+        svg,
+        "<g>{0}</g><g transform='rotate(180 {1} {2})'>{0}</g>",
+        content, CENTER_X, CENTER_Y
+    )
+    .unwrap();
+}
+
+/// Positions of the pips of the numbered cards, from 2 to 10.
+fn pip_positions(number: u8) -> Vec<(f64, f64)> {
+    const LEFT: f64 = 80.0;
+    const RIGHT: f64 = 170.0;
+    const TOP: f64 = 75.0;
+    const BOTTOM: f64 = 275.0;
+
+    let column = |x, rows: &[f64]| rows.iter().map(move |y| (x, *y)).collect::<Vec<_>>();
+    let sides = |rows: &[f64]| [column(LEFT, rows), column(RIGHT, rows)].concat();
+
+    let two_rows = [TOP, BOTTOM];
+    let three_rows = [TOP, CENTER_Y, BOTTOM];
+    let four_rows = [TOP, 142.0, 208.0, BOTTOM];
+    match number {
+        2 => column(CENTER_X, &two_rows),
+        3 => column(CENTER_X, &three_rows),
+        4 => sides(&two_rows),
+        5 => [sides(&two_rows), column(CENTER_X, &[CENTER_Y])].concat(),
+        6 => sides(&three_rows),
+        7 => [sides(&three_rows), column(CENTER_X, &[125.0])].concat(),
+        8 => [sides(&three_rows), column(CENTER_X, &[125.0, 225.0])].concat(),
+        9 => [sides(&four_rows), column(CENTER_X, &[CENTER_Y])].concat(),
+        10 => [sides(&four_rows), column(CENTER_X, &[108.0, 242.0])].concat(),
+        _ => vec![],
+    }
+}
+
+fn write_standard(svg: &mut String, rank: Rank, suit: Suit) {
+    write_suit_symbol(svg, suit);
+    write!(svg, "<g fill='{}'>", suit.ink()).unwrap();
+
+    let corner = format!(
+        "<text x='24' y='44' font-size='36'>{}</text>\
+         <use href='#pip' x='12' y='52' width='24' height='24'/>",
+        rank.label()
+    );
+    write_corners(svg, &corner);
+
+    match rank {
+        Rank::Ace => write_pip(svg, CENTER_X, CENTER_Y, 110.0),
+        Rank::Number(number) => {
+            for (x, y) in pip_positions(number) {
+                write_pip(svg, x, y, 44.0);
+            }
+        }
+        Rank::Jack | Rank::Queen | Rank::King => {
+            write!(
+                svg,
+                "<rect x='55' y='60' width='140' height='230' rx='8' fill='none' \
+                 stroke='{}' stroke-width='3'/>\
+                 <text x='{}' y='{}' font-size='120'>{}</text>",
+                suit.ink(),
+                CENTER_X,
+                CENTER_Y + 42.0,
+                rank.label()
+            )
+            .unwrap();
+            write_pip(svg, 85.0, 92.0, 36.0);
+            write_pip(svg, 165.0, 258.0, 36.0);
+        }
+    }
+    svg.push_str("</g>");
+}
+
+fn write_joker(svg: &mut String, red: bool) {
+    let ink = if red { RED_INK } else { DEFAULT_INK };
+    write!(svg, "<g fill='{}'>", ink).unwrap();
+
+    let mut corner = String::from("<text x='24' y='20' font-size='26'>");
+    for letter in "JOKER".chars() {
+        write!(corner, "<tspan x='24' dy='26'>{}</tspan>", letter).unwrap();
+    }
+    corner.push_str("</text>");
+    write_corners(svg, &corner);
+
+    // A five-pointed star, alternating between the outer and inner points.
+    let points = (0..10)
+        .map(|i| {
+            let radius = if i % 2 == 0 { 80.0 } else { 32.0 };
+            let angle = std::f64::consts::PI * (i as f64) / 5.0;
+            format!(
+                "{:.1},{:.1}",
+                CENTER_X + radius * angle.sin(),
+                CENTER_Y - 20.0 - radius * angle.cos()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    write!(
+        svg,
+        "<polygon points='{}'/><text x='{}' y='{}' font-size='40'>JOKER</text></g>",
+        points,
+        CENTER_X,
+        CENTER_Y + 110.0
+    )
+    .unwrap();
+}
+
+fn write_colored(svg: &mut String, value: &str, color: Option<&str>) {
+    let color = escape(color.unwrap_or(DEFAULT_INK));
+    let value = escape(value);
+    // Long values, e.g. "+4" or "skip", have to be smaller to fit in the oval.
+    let font_size = match value.chars().count() {
+        0..=2 => 110.0,
+        3..=4 => 70.0,
+        _ => 40.0,
+    };
+
+    write!(
+        svg,
+        "<rect x='14' y='14' width='{w}' height='{h}' rx='10' fill='{c}'/>\
+         <ellipse cx='{cx}' cy='{cy}' rx='80' ry='125' transform='rotate(30 {cx} {cy})' fill='white'/>\
+         <text x='{cx}' y='{ty}' font-size='{fs}' fill='{c}'>{v}</text>",
+        w = WIDTH - 28.0,
+        h = HEIGHT - 28.0,
+        c = color,
+        cx = CENTER_X,
+        cy = CENTER_Y,
+        ty = CENTER_Y + font_size * 0.35,
+        fs = font_size,
+        v = value,
+    )
+    .unwrap();
+
+    let corner = format!(
+        "<text x='40' y='60' font-size='36' fill='white'>{}</text>",
+        value
+    );
+    write_corners(svg, &corner);
+}
+
+/// Escapes text and attribute values, which are quoted with `'`.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\'' => escaped.push_str("&apos;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_the_pips() {
+        for number in 2..=10 {
+            let svg = Face::Standard {
+                rank: Rank::Number(number),
+                suit: Suit::Clubs,
+            }
+            .to_svg();
+            // Each corner also has a pip.
+            assert_eq!(svg.matches("<use ").count(), number as usize + 2);
+        }
+    }
+
+    #[test]
+    fn escapes_server_strings() {
+        let svg = Face::Colored {
+            value: "<b>".to_string(),
+            color: Some("red' onload='alert(1)".to_string()),
+        }
+        .to_svg();
+        assert!(!svg.contains("<b>"));
+        assert!(!svg.contains("red' onload"));
+
+        let back = BackTheme::new(BackPattern::Checkers, "a'b", "c").to_svg();
+        assert!(back.contains("a&apos;b"));
+    }
+
+    #[test]
+    fn data_urls_are_encoded() {
+        let url = data_url(&BackTheme::default().to_svg());
+        assert!(url.starts_with("data:image/svg+xml,%3Csvg"));
+        assert!(!url.contains(|c: char| "<>#\" ".contains(c)));
+        assert!(url.contains("%23"));
+    }
+}
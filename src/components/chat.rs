@@ -0,0 +1,497 @@
+//! Text chat between the players of a game, sent over the game WebSocket.
+
+use std::collections::VecDeque;
+use wasm_bindgen::JsValue;
+use yew::prelude::*;
+use yewtil::NeqAssign;
+
+use crate::agents::game_mgr::{GameMgr, GameMgrRequest, GameMgrResponse};
+use crate::agents::settings_bus::SettingsBus;
+use crate::game::Player;
+use crate::settings::Settings;
+use crate::wire::{self, ChatTarget};
+
+/// A player can send at most `RATE_LIMIT_MESSAGES` messages every
+/// `RATE_LIMIT_PERIOD` milliseconds. The server is free to be stricter.
+const RATE_LIMIT_MESSAGES: usize = 5;
+const RATE_LIMIT_PERIOD: f64 = 10_000.0;
+
+/// Maximum length of a message, in characters.
+const MAX_MESSAGE_LENGTH: usize = 500;
+
+/// Number of messages kept, the oldest ones being dropped.
+const MAX_MESSAGES: usize = 200;
+
+/// The emojis typed as `:shortcode:`.
+const EMOJIS: &[(&str, &str)] = &[
+    ("smile", "😄"),
+    ("grin", "😁"),
+    ("joy", "😂"),
+    ("wink", "😉"),
+    ("sunglasses", "😎"),
+    ("thinking", "🤔"),
+    ("cry", "😢"),
+    ("angry", "😠"),
+    ("scream", "😱"),
+    ("heart", "❤️"),
+    ("+1", "👍"),
+    ("thumbsup", "👍"),
+    ("-1", "👎"),
+    ("thumbsdown", "👎"),
+    ("clap", "👏"),
+    ("wave", "👋"),
+    ("ok_hand", "👌"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("tada", "🎉"),
+    ("trophy", "🏆"),
+    ("spades", "♠️"),
+    ("hearts", "♥️"),
+    ("diamonds", "♦️"),
+    ("clubs", "♣️"),
+];
+
+/// Replaces the known `:shortcode:`s of a text by their emoji.
+pub fn expand_shortcodes(text: &str) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let emoji = after.find(':').and_then(|end| {
+            EMOJIS
+                .iter()
+                .find(|(shortcode, _)| *shortcode == &after[..end])
+                .map(|(_, emoji)| (*emoji, end))
+        });
+        match emoji {
+            Some((emoji, end)) => {
+                expanded.push_str(emoji);
+                rest = &after[end + 1..];
+            }
+            None => {
+                // The colon may open the next shortcode.
+                expanded.push(':');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Keeps track of the messages sent recently.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    sent: VecDeque<f64>,
+}
+
+impl RateLimiter {
+    /// Records a message sent at the given time, in milliseconds, or returns
+    /// how long to wait before it can be sent.
+    pub fn try_send(&mut self, now: f64) -> Result<(), f64> {
+        while let Some(oldest) = self.sent.front() {
+            if now - oldest < RATE_LIMIT_PERIOD {
+                break;
+            }
+            self.sent.pop_front();
+        }
+        match self.sent.front() {
+            Some(oldest) if self.sent.len() >= RATE_LIMIT_MESSAGES => {
+                Err(oldest + RATE_LIMIT_PERIOD - now)
+            }
+            _ => {
+                self.sent.push_back(now);
+                Ok(())
+            }
+        }
+    }
+}
+
+pub struct Chat {
+    link: ComponentLink<Self>,
+    props: Props,
+    messages_ref: NodeRef,
+
+    game_mgr_agent: Box<dyn Bridge<GameMgr>>,
+    /// Holds the players muted by the player.
+    settings: Settings,
+    settings_bus: Box<dyn Bridge<SettingsBus>>,
+
+    messages: VecDeque<wire::ChatMessage>,
+    /// Messages received while the chat was collapsed.
+    unread: usize,
+    collapsed: bool,
+
+    draft: String,
+    target: Option<ChatTarget>,
+    rate_limiter: RateLimiter,
+    error: Option<String>,
+}
+
+#[derive(Properties, Clone, Debug, PartialEq)]
+pub struct Props {
+    pub player_id: String,
+    /// The players who can be sent private messages, also used to show the
+    /// names of the senders.
+    #[prop_or_default]
+    pub players: Vec<Player>,
+}
+
+pub enum Msg {
+    GameMgrMessage(GameMgrResponse),
+    DraftChanged(String),
+    SetTarget(Option<ChatTarget>),
+    Send,
+    ToggleCollapsed,
+    Mute(String),
+    Unmute(String),
+    SettingsChanged(Settings),
+    Ignore,
+}
+
+impl Component for Chat {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let game_mgr_agent = GameMgr::bridge(link.callback(Msg::GameMgrMessage));
+        let settings_bus = SettingsBus::bridge(link.callback(Msg::SettingsChanged));
+        Chat {
+            link,
+            props,
+            messages_ref: NodeRef::default(),
+
+            game_mgr_agent,
+            settings: Settings::load(),
+            settings_bus,
+
+            messages: VecDeque::new(),
+            unread: 0,
+            collapsed: false,
+
+            draft: String::new(),
+            target: None,
+            rate_limiter: RateLimiter::default(),
+            error: None,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        self.props.neq_assign(props)
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::GameMgrMessage(GameMgrResponse::ChatMessageReceived(message)) => {
+                if self.settings.muted_players.contains(&message.sender) {
+                    return false;
+                }
+                if self.collapsed && message.sender != self.props.player_id {
+                    self.unread += 1;
+                }
+                if self.messages.len() >= MAX_MESSAGES {
+                    self.messages.pop_front();
+                }
+                self.messages.push_back(message);
+                true
+            }
+            Msg::GameMgrMessage(_) => false,
+            Msg::DraftChanged(draft) => {
+                self.draft = draft;
+                self.error = None;
+                true
+            }
+            Msg::SetTarget(target) => self.target.neq_assign(target),
+            Msg::Send => {
+                self.send();
+                true
+            }
+            Msg::ToggleCollapsed => {
+                self.collapsed = !self.collapsed;
+                self.unread = 0;
+                true
+            }
+            Msg::Mute(player) => {
+                self.settings.muted_players.insert(player);
+                self.save_settings();
+                true
+            }
+            Msg::Unmute(player) => {
+                self.settings.muted_players.remove(&player);
+                self.save_settings();
+                true
+            }
+            Msg::SettingsChanged(settings) => self.settings.neq_assign(settings),
+            Msg::Ignore => false,
+        }
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        // Keeps the last message in sight.
+        if let Some(messages) = self.messages_ref.cast::<web_sys::Element>() {
+            messages.set_scroll_top(messages.scroll_height());
+        }
+    }
+
+    fn view(&self) -> Html {
+        let unread = match self.unread {
+            0 => html! {},
+            unread => html! { <span class="tag is-danger is-rounded">{ unread }</span> },
+        };
+
+        html! {
+            <div class="card game-chat">
+                <header class="card-header">
+                    <p class="card-header-title">{ "Chat" }{ unread }</p>
+                    <a
+                        class="card-header-icon"
+                        aria-label=if self.collapsed { "Show the chat" } else { "Hide the chat" }
+                        onclick=self.link.callback(|_| Msg::ToggleCollapsed)
+                        >
+                        { if self.collapsed { "▸" } else { "▾" } }
+                    </a>
+                </header>
+                { self.render_content() }
+            </div>
+        }
+    }
+}
+
+impl Chat {
+    fn send(&mut self) {
+        let text = expand_shortcodes(self.draft.trim());
+        if text.is_empty() {
+            return;
+        }
+        if text.chars().count() > MAX_MESSAGE_LENGTH {
+            self.error = Some(format!(
+                "Messages are limited to {} characters.",
+                MAX_MESSAGE_LENGTH
+            ));
+            return;
+        }
+        if let Err(wait) = self.rate_limiter.try_send(js_sys::Date::now()) {
+            self.error = Some(format!(
+                "You are sending messages too fast, wait {} seconds.",
+                (wait / 1000.0).ceil()
+            ));
+            return;
+        }
+
+        self.game_mgr_agent
+            .send(GameMgrRequest::SendChatMessage(wire::SendChatMessage {
+                text,
+                target: self.target.clone(),
+            }));
+        self.draft.clear();
+        self.error = None;
+    }
+
+    fn render_content(&self) -> Html {
+        if self.collapsed {
+            return html! {};
+        }
+
+        let error = match &self.error {
+            Some(error) => html! { <p class="help is-danger">{ error }</p> },
+            None => html! {},
+        };
+        html! {
+            <div class="card-content">
+                <div class="game-chat-messages" ref=self.messages_ref.clone() role="log" aria-label="Chat messages">
+                    { for self.messages.iter().map(|message| self.render_message(message)) }
+                </div>
+                { self.render_input() }
+                { error }
+                { self.render_muted_players() }
+            </div>
+        }
+    }
+
+    fn render_message(&self, message: &wire::ChatMessage) -> Html {
+        if self.settings.muted_players.contains(&message.sender) {
+            return html! {};
+        }
+
+        let (class, recipient) = match &message.target {
+            Some(ChatTarget::Team) => ("is-team", " to the team".to_string()),
+            Some(ChatTarget::Player { player_id }) => {
+                ("is-private", format!(" to {}", self.player_name(player_id)))
+            }
+            _ => ("", String::new()),
+        };
+        let sender_name = self.player_name(&message.sender);
+        let mute = if message.sender == self.props.player_id {
+            html! {}
+        } else {
+            let sender = message.sender.clone();
+            html! {
+                <a
+                    class="game-chat-mute"
+                    title=format!("Mute {}", sender_name)
+                    onclick=self.link.callback(move |_| Msg::Mute(sender.clone()))
+                    >
+                    { "mute" }
+                </a>
+            }
+        };
+
+        html! {
+            <p class=("game-chat-message", class)>
+                <time class="game-chat-time">{ format_time(message.timestamp) }</time>
+                <strong>{ sender_name }</strong>
+                { recipient }
+                { mute }
+                { ": " }
+                { &message.text }
+            </p>
+        }
+    }
+
+    fn render_input(&self) -> Html {
+        let recipients: Vec<Option<ChatTarget>> = vec![None, Some(ChatTarget::Team)]
+            .into_iter()
+            .chain(
+                self.props
+                    .players
+                    .iter()
+                    .filter(|player| player.id != self.props.player_id)
+                    .map(|player| {
+                        Some(ChatTarget::Player {
+                            player_id: player.id.clone(),
+                        })
+                    }),
+            )
+            .collect();
+        let onchange = {
+            let recipients = recipients.clone();
+            self.link.callback(move |event: ChangeData| match event {
+                ChangeData::Select(select) => {
+                    let index = select.selected_index().max(0) as usize;
+                    Msg::SetTarget(recipients.get(index).cloned().flatten())
+                }
+                _ => Msg::Ignore,
+            })
+        };
+        let recipient_name = |target: &Option<ChatTarget>| match target {
+            None => "Everyone".to_string(),
+            Some(ChatTarget::Team) => "Team".to_string(),
+            Some(ChatTarget::Player { player_id }) => self.player_name(player_id).to_string(),
+            Some(ChatTarget::Unknown { r#type, .. }) => r#type.clone(),
+        };
+        let onkeypress = self.link.callback(|event: KeyboardEvent| {
+            if event.key() == "Enter" {
+                Msg::Send
+            } else {
+                Msg::Ignore
+            }
+        });
+
+        html! {
+            <div class="field has-addons">
+                <div class="control">
+                    <div class="select is-small">
+                        <select onchange=onchange aria-label="Recipients">
+                            {
+                                for recipients.iter().map(|target| html! {
+                                    <option selected=*target == self.target>
+                                        { recipient_name(target) }
+                                    </option>
+                                })
+                            }
+                        </select>
+                    </div>
+                </div>
+                <div class="control is-expanded">
+                    <input
+                        class="input is-small"
+                        type="text"
+                        placeholder="Message, :smile: for emojis"
+                        aria-label="Message"
+                        value=&self.draft
+                        oninput=self.link.callback(|e: InputData| Msg::DraftChanged(e.value))
+                        onkeypress=onkeypress
+                        />
+                </div>
+                <div class="control">
+                    <button class="button is-small is-primary" onclick=self.link.callback(|_| Msg::Send)>
+                        { "Send" }
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_muted_players(&self) -> Html {
+        if self.settings.muted_players.is_empty() {
+            return html! {};
+        }
+        let render_player = |player_id: &String| {
+            let onclick = {
+                let player_id = player_id.clone();
+                self.link.callback(move |_| Msg::Unmute(player_id.clone()))
+            };
+            let name = self.player_name(player_id);
+            html! {
+                <span class="tag">
+                    { name }
+                    <button class="delete is-small" aria-label=format!("Unmute {}", name) onclick=onclick></button>
+                </span>
+            }
+        };
+
+        html! {
+            <div class="tags game-chat-muted">
+                <span class="help">{ "Muted:" }</span>
+                { for self.settings.muted_players.iter().map(render_player) }
+            </div>
+        }
+    }
+
+    /// Saves the settings and tells the other components, so that they don't
+    /// save a stale copy.
+    fn save_settings(&mut self) {
+        self.settings.save();
+        self.settings_bus.send(self.settings.clone());
+    }
+
+    /// Returns the name of a player, or their ID if they left the game.
+    fn player_name<'a>(&'a self, player_id: &'a str) -> &'a str {
+        self.props
+            .players
+            .iter()
+            .find(|player| player.id == player_id)
+            .map_or(player_id, |player| &player.name)
+    }
+}
+
+/// Formats a timestamp in milliseconds as the local time of day.
+fn format_time(timestamp: u64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(timestamp as f64));
+    format!("{:02}:{:02}", date.get_hours(), date.get_minutes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_shortcodes() {
+        assert_eq!(expand_shortcodes("gg :tada:"), "gg 🎉");
+        assert_eq!(expand_shortcodes(":+1::fire:"), "👍🔥");
+        assert_eq!(expand_shortcodes("at 10:30 :smile:"), "at 10:30 😄");
+        assert_eq!(expand_shortcodes(":unknown: :"), ":unknown: :");
+    }
+
+    #[test]
+    fn limits_the_rate_of_messages() {
+        let mut limiter = RateLimiter::default();
+        for i in 0..RATE_LIMIT_MESSAGES {
+            assert_eq!(limiter.try_send(i as f64 * 1000.0), Ok(()));
+        }
+        assert_eq!(limiter.try_send(5000.0), Err(5000.0));
+        assert_eq!(limiter.try_send(10_000.0), Ok(()));
+        assert!(limiter.try_send(10_500.0).is_err());
+    }
+}
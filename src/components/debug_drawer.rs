@@ -0,0 +1,151 @@
+//! The drawer holding the debug tools, shown on demand.
+
+use yew::prelude::*;
+use yew::services::keyboard::{KeyListenerHandle, KeyboardService};
+use yewtil::NeqAssign;
+
+use crate::agents::settings_bus::SettingsBus;
+use crate::components::{StateInspector, TrafficStatsView, WebSocketDebugConsole};
+use crate::settings::{DebugConsoleDock, Settings};
+
+/// The keys showing and hiding the drawer, as told to the user.
+pub const SHORTCUT: &str = "Ctrl + `";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tab {
+    Messages,
+    GameState,
+    Traffic,
+}
+
+pub struct DebugDrawer {
+    link: ComponentLink<Self>,
+
+    tab: Tab,
+
+    settings: Settings,
+    settings_bus: Box<dyn Bridge<SettingsBus>>,
+    _key_listener: Option<KeyListenerHandle>,
+}
+
+pub enum Msg {
+    KeyDown(KeyboardEvent),
+    Toggle,
+    SelectTab(Tab),
+    Dock(DebugConsoleDock),
+    SettingsChanged(Settings),
+}
+
+impl Component for DebugDrawer {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let key_listener = web_sys::window()
+            .map(|window| KeyboardService::register_key_down(&window, link.callback(Msg::KeyDown)));
+
+        let settings_bus = SettingsBus::bridge(link.callback(Msg::SettingsChanged));
+
+        DebugDrawer {
+            link,
+
+            tab: Tab::Messages,
+            settings: Settings::load(),
+            settings_bus,
+            _key_listener: key_listener,
+        }
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let debug_console = &mut self.settings.debug_console;
+        match msg {
+            Msg::KeyDown(event) => {
+                // Matches the physical key, since the backquote moves between
+                // keyboard layouts.
+                if !(event.ctrl_key() && event.code() == "Backquote") {
+                    return false;
+                }
+                event.prevent_default();
+                debug_console.visible = !debug_console.visible;
+            }
+            Msg::Toggle => debug_console.visible = !debug_console.visible,
+            Msg::SelectTab(tab) => return self.tab.neq_assign(tab),
+            Msg::Dock(dock) => debug_console.dock = dock,
+            Msg::SettingsChanged(settings) => return self.settings.neq_assign(settings),
+        }
+        self.settings.save();
+        self.settings_bus.send(self.settings.clone());
+        true
+    }
+
+    fn view(&self) -> Html {
+        let debug_console = &self.settings.debug_console;
+        let hidden = if debug_console.visible {
+            ""
+        } else {
+            "is-hidden"
+        };
+
+        let (dock_class, other_dock, other_dock_name) = match debug_console.dock {
+            DebugConsoleDock::Bottom => ("is-bottom", DebugConsoleDock::Right, "Dock on the right"),
+            DebugConsoleDock::Right => ("is-right", DebugConsoleDock::Bottom, "Dock at the bottom"),
+        };
+
+        // The drawer and its panes stay mounted while hidden, to keep the
+        // history of the console.
+        let tab = |tab: Tab, name: &str| {
+            html! {
+                <li class=if self.tab == tab { "is-active" } else { "" }>
+                    <a
+                        role="tab"
+                        aria-selected=if self.tab == tab { "true" } else { "false" }
+                        onclick=self.link.callback(move |_| Msg::SelectTab(tab))
+                        >
+                        { name }
+                    </a>
+                </li>
+            }
+        };
+        let hidden_unless = |tab: Tab| if self.tab == tab { "" } else { "is-hidden" };
+
+        html! {
+            <aside class=("debug-drawer", dock_class, hidden) aria-label="Debug tools">
+                <header class="debug-drawer-header">
+                    <div class="tabs is-small" role="tablist">
+                        <ul>
+                            { tab(Tab::Messages, "Messages") }
+                            { tab(Tab::GameState, "Game state") }
+                            { tab(Tab::Traffic, "Traffic") }
+                        </ul>
+                    </div>
+                    <div class="buttons">
+                        <button
+                            class="button is-small"
+                            onclick=self.link.callback(move |_| Msg::Dock(other_dock))
+                            >
+                            { other_dock_name }
+                        </button>
+                        <button
+                            class="delete"
+                            aria-label=format!("Close ({})", SHORTCUT)
+                            onclick=self.link.callback(|_| Msg::Toggle)
+                            ></button>
+                    </div>
+                </header>
+                <div class=("debug-drawer-content", hidden_unless(Tab::Messages)) role="tabpanel">
+                    <WebSocketDebugConsole />
+                </div>
+                <div class=("debug-drawer-content", hidden_unless(Tab::GameState)) role="tabpanel">
+                    <StateInspector />
+                </div>
+                <div class=("debug-drawer-content", hidden_unless(Tab::Traffic)) role="tabpanel">
+                    <TrafficStatsView />
+                </div>
+            </aside>
+        }
+    }
+}
@@ -0,0 +1,442 @@
+//! The history of the WebSocket debug console, kept as structured entries so
+//! that it can be filtered, searched and pretty-printed.
+//!
+//! The history can be exported to attach it to bug reports, either as JSON
+//! Lines, whose first line holds the metadata of the connection and each of the
+//! following lines an entry, or as a HAR-like file, shaped like the ones the
+//! browsers save with the WebSocket frames of a page.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+use crate::jsonl;
+use crate::wire;
+
+/// The version of the HAR format the exported files follow.
+const HAR_VERSION: &str = "1.2";
+/// The WebSocket opcode of text frames.
+const TEXT_OPCODE: u8 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryDirection {
+    Sent,
+    Received,
+    /// Things happening to the connection itself.
+    Local,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryContent {
+    Message(wire::Message),
+    /// Data which couldn't be decoded or sent.
+    Error(String),
+    /// A change of the connection, like connecting or closing.
+    Event(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(from = "EntryFields")]
+pub struct HistoryEntry {
+    pub id: usize,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+    pub direction: EntryDirection,
+    #[serde(flatten)]
+    pub content: EntryContent,
+    /// Computed once, since the filters and the console look at them on
+    /// every render.
+    #[serde(skip)]
+    message_type: Option<String>,
+    #[serde(skip)]
+    summary: String,
+}
+
+/// The fields of an exported entry, without the ones computed from them.
+#[derive(Deserialize)]
+struct EntryFields {
+    id: usize,
+    timestamp: f64,
+    direction: EntryDirection,
+    #[serde(flatten)]
+    content: EntryContent,
+}
+
+impl From<EntryFields> for HistoryEntry {
+    fn from(fields: EntryFields) -> Self {
+        HistoryEntry::new(
+            fields.id,
+            fields.timestamp,
+            fields.direction,
+            fields.content,
+        )
+    }
+}
+
+/// Tells where an exported history comes from.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct HistoryMetadata {
+    pub game_id: String,
+    pub player_id: String,
+    /// The commit the client was built from.
+    pub commit: String,
+}
+
+impl HistoryEntry {
+    pub fn new(
+        id: usize,
+        timestamp: f64,
+        direction: EntryDirection,
+        content: EntryContent,
+    ) -> Self {
+        let (message_type, summary) = match &content {
+            EntryContent::Message(message) => (
+                Some(message.type_tag().to_string()),
+                serde_json::to_string(message).unwrap_or_else(|_| format!("{:?}", message)),
+            ),
+            EntryContent::Error(error) => (None, format!("ERROR: {}", error)),
+            EntryContent::Event(event) => (None, event.clone()),
+        };
+        HistoryEntry {
+            id,
+            timestamp,
+            direction,
+            content,
+            message_type,
+            summary,
+        }
+    }
+
+    /// The type of the message, if the entry holds one.
+    pub fn message_type(&self) -> Option<&str> {
+        self.message_type.as_deref()
+    }
+
+    /// The entry on a single line.
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    /// The entry indented over several lines, if it's worth expanding.
+    pub fn pretty(&self) -> Option<String> {
+        match &self.content {
+            EntryContent::Message(message) => serde_json::to_string_pretty(message).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryFilter {
+    pub sent: bool,
+    pub received: bool,
+    pub local: bool,
+    /// Only shows messages of this type.
+    pub message_type: Option<String>,
+    /// Only shows entries containing this text, whatever the case.
+    pub search: String,
+}
+
+impl Default for HistoryFilter {
+    fn default() -> Self {
+        HistoryFilter {
+            sent: true,
+            received: true,
+            local: true,
+            message_type: None,
+            search: String::new(),
+        }
+    }
+}
+
+impl HistoryFilter {
+    pub fn shows_direction(&self, direction: EntryDirection) -> bool {
+        match direction {
+            EntryDirection::Sent => self.sent,
+            EntryDirection::Received => self.received,
+            EntryDirection::Local => self.local,
+        }
+    }
+
+    pub fn toggle_direction(&mut self, direction: EntryDirection) {
+        match direction {
+            EntryDirection::Sent => self.sent = !self.sent,
+            EntryDirection::Received => self.received = !self.received,
+            EntryDirection::Local => self.local = !self.local,
+        }
+    }
+
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        if !self.shows_direction(entry.direction) {
+            return false;
+        }
+        if let Some(message_type) = &self.message_type {
+            if entry.message_type() != Some(message_type.as_str()) {
+                return false;
+            }
+        }
+        let search = self.search.trim().to_lowercase();
+        search.is_empty() || entry.summary().to_lowercase().contains(&search)
+    }
+}
+
+/// The types of the messages in the history, to filter on them.
+pub fn message_types<'a>(entries: impl IntoIterator<Item = &'a HistoryEntry>) -> BTreeSet<String> {
+    entries
+        .into_iter()
+        .filter_map(HistoryEntry::message_type)
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn to_jsonl<'a>(
+    metadata: &HistoryMetadata,
+    entries: impl IntoIterator<Item = &'a HistoryEntry>,
+) -> String {
+    jsonl::write(metadata, entries)
+}
+
+/// Exports the history as a single HAR entry holding the WebSocket messages.
+/// The events of the connection don't fit in the format and are left out.
+pub fn to_har<'a>(
+    metadata: &HistoryMetadata,
+    entries: impl IntoIterator<Item = &'a HistoryEntry>,
+    started_date_time: &str,
+) -> String {
+    let messages = entries
+        .into_iter()
+        .filter_map(|entry| {
+            let r#type = match entry.direction {
+                EntryDirection::Sent => "send",
+                EntryDirection::Received => "receive",
+                EntryDirection::Local => return None,
+            };
+            let mut message = json!({
+                "type": r#type,
+                "time": entry.timestamp / 1000.0,
+                "opcode": TEXT_OPCODE,
+            });
+            match &entry.content {
+                EntryContent::Message(data) => {
+                    message["data"] = serde_json::to_string(data).unwrap_or_default().into()
+                }
+                EntryContent::Error(error) | EntryContent::Event(error) => {
+                    message["data"] = "".into();
+                    message["_error"] = error.as_str().into();
+                }
+            }
+            Some(message)
+        })
+        .collect::<Vec<_>>();
+
+    let har = json!({
+        "log": {
+            "version": HAR_VERSION,
+            "creator": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": metadata.commit,
+            },
+            "entries": [{
+                "startedDateTime": started_date_time,
+                "request": {
+                    "method": "GET",
+                    "queryString": [
+                        { "name": "game_id", "value": metadata.game_id },
+                        { "name": "player_id", "value": metadata.player_id },
+                    ],
+                },
+                "_gameId": metadata.game_id,
+                "_playerId": metadata.player_id,
+                "_webSocketMessages": messages,
+            }],
+        },
+    });
+    serde_json::to_string_pretty(&har).unwrap_or_default()
+}
+
+/// Reads a history exported either as JSON Lines or as a HAR-like file.
+pub fn import(text: &str) -> Result<(HistoryMetadata, Vec<HistoryEntry>), String> {
+    match serde_json::from_str::<Value>(text) {
+        Ok(har) if har.get("log").is_some() => import_har(&har),
+        _ => jsonl::read(text, "file", "metadata"),
+    }
+}
+
+fn import_har(har: &Value) -> Result<(HistoryMetadata, Vec<HistoryEntry>), String> {
+    let har_entry = &har["log"]["entries"][0];
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    let metadata = HistoryMetadata {
+        game_id: text(&har_entry["_gameId"]),
+        player_id: text(&har_entry["_playerId"]),
+        commit: text(&har["log"]["creator"]["version"]),
+    };
+    let messages = har_entry["_webSocketMessages"]
+        .as_array()
+        .ok_or_else(|| "The HAR file has no WebSocket messages.".to_string())?;
+
+    let entries = messages
+        .iter()
+        .enumerate()
+        .map(|(index, message)| {
+            let direction = match message["type"].as_str() {
+                Some("send") => EntryDirection::Sent,
+                Some("receive") => EntryDirection::Received,
+                _ => return Err(format!("Message {}: unknown type", index + 1)),
+            };
+            let data = message["data"].as_str().unwrap_or_default();
+            let content = match message["_error"].as_str() {
+                Some(error) => EntryContent::Error(error.to_string()),
+                None => match serde_json::from_str(data) {
+                    Ok(message) => EntryContent::Message(message),
+                    Err(e) => EntryContent::Error(format!("{}: {}", e, data)),
+                },
+            };
+            Ok(HistoryEntry::new(
+                index + 1,
+                message["time"].as_f64().unwrap_or_default() * 1000.0,
+                direction,
+                content,
+            ))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((metadata, entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: usize, direction: EntryDirection, content: EntryContent) -> HistoryEntry {
+        HistoryEntry::new(id, 0.0, direction, content)
+    }
+
+    #[test]
+    fn filters_entries() {
+        let entries = vec![
+            entry(
+                1,
+                EntryDirection::Local,
+                EntryContent::Event("Connected".into()),
+            ),
+            entry(
+                2,
+                EntryDirection::Sent,
+                EntryContent::Message(wire::Message::Ping),
+            ),
+            entry(
+                3,
+                EntryDirection::Received,
+                EntryContent::Message(wire::Message::Pong),
+            ),
+            entry(
+                4,
+                EntryDirection::Received,
+                EntryContent::Error("bad".into()),
+            ),
+        ];
+        let shown = |filter: &HistoryFilter| {
+            entries
+                .iter()
+                .filter(|entry| filter.matches(entry))
+                .map(|entry| entry.id)
+                .collect::<Vec<_>>()
+        };
+
+        let mut filter = HistoryFilter::default();
+        assert_eq!(shown(&filter), vec![1, 2, 3, 4]);
+
+        filter.toggle_direction(EntryDirection::Received);
+        assert_eq!(shown(&filter), vec![1, 2]);
+
+        filter = HistoryFilter {
+            message_type: Some("PONG".to_string()),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(shown(&filter), vec![3]);
+
+        filter = HistoryFilter {
+            search: " Bad".to_string(),
+            ..HistoryFilter::default()
+        };
+        assert_eq!(shown(&filter), vec![4]);
+
+        assert_eq!(
+            message_types(&entries).into_iter().collect::<Vec<_>>(),
+            vec!["PING", "PONG"]
+        );
+    }
+
+    #[test]
+    fn prints_messages() {
+        let ping = entry(
+            1,
+            EntryDirection::Sent,
+            EntryContent::Message(wire::Message::Ping),
+        );
+        assert_eq!(ping.summary(), r#"{"type":"PING"}"#);
+        assert_eq!(ping.pretty().as_deref(), Some("{\n  \"type\": \"PING\"\n}"));
+
+        let event = entry(
+            2,
+            EntryDirection::Local,
+            EntryContent::Event("Closed".into()),
+        );
+        assert_eq!(event.summary(), "Closed");
+        assert_eq!(event.pretty(), None);
+    }
+
+    #[test]
+    fn exports_and_imports_histories() {
+        let metadata = HistoryMetadata {
+            game_id: "round".to_string(),
+            player_id: "p1".to_string(),
+            commit: "abc123".to_string(),
+        };
+        let entries = vec![
+            entry(
+                1,
+                EntryDirection::Local,
+                EntryContent::Event("Connected".into()),
+            ),
+            entry(
+                2,
+                EntryDirection::Sent,
+                EntryContent::Message(wire::Message::Ping),
+            ),
+            entry(
+                3,
+                EntryDirection::Received,
+                EntryContent::Error("bad".into()),
+            ),
+        ];
+
+        let jsonl = to_jsonl(&metadata, &entries);
+        assert_eq!(
+            jsonl.lines().nth(2),
+            Some(r#"{"id":2,"timestamp":0.0,"direction":"sent","message":{"type":"PING"}}"#)
+        );
+        assert_eq!(import(&jsonl), Ok((metadata.clone(), entries.clone())));
+
+        let har = to_har(&metadata, &entries, "2020-06-01T12:00:00.000Z");
+        let (har_metadata, har_entries) = import(&har).unwrap();
+        assert_eq!(har_metadata, metadata);
+        // The events of the connection are left out.
+        assert_eq!(
+            har_entries
+                .into_iter()
+                .map(|entry| (entry.direction, entry.content))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    EntryDirection::Sent,
+                    EntryContent::Message(wire::Message::Ping)
+                ),
+                (EntryDirection::Received, EntryContent::Error("bad".into())),
+            ]
+        );
+
+        assert_eq!(import(""), Err("The file is empty.".to_string()));
+    }
+}
@@ -0,0 +1,186 @@
+//! Scripts of the WebSocket debug console: named sequences of messages to
+//! send, with delays and waits for the answers of the server, kept in the local
+//! storage of the browser to replay scenarios quickly.
+//!
+//! Scripts are written as JSON lists of steps, e.g.
+//! `[{"send": {"type": "START_GAME"}}, {"wait_for": "GAME_STARTED"}, {"delay": 500}]`.
+
+use serde::{Deserialize, Serialize};
+use yew::format::Json;
+use yew::services::storage::{Area, StorageService};
+
+use crate::wire;
+
+const KEY: &str = "cards-client-rs.debug-scripts";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptStep {
+    Send(wire::Message),
+    /// Waits for the given number of milliseconds.
+    Delay(u32),
+    /// Waits until a message of the given type is received.
+    WaitFor(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Script {
+    pub name: String,
+    pub steps: Vec<ScriptStep>,
+}
+
+impl Script {
+    /// Reads the steps of a script, written as a JSON list.
+    pub fn parse_steps(text: &str) -> Result<Vec<ScriptStep>, String> {
+        let steps = serde_json::from_str::<Vec<ScriptStep>>(text).map_err(|e| e.to_string())?;
+        for (index, step) in steps.iter().enumerate() {
+            if let ScriptStep::Send(wire::Message::Unknown { r#type, .. }) = step {
+                return Err(format!(
+                    "Step {}: unknown message type {:?}",
+                    index + 1,
+                    r#type
+                ));
+            }
+        }
+        Ok(steps)
+    }
+
+    pub fn steps_to_string(&self) -> String {
+        serde_json::to_string_pretty(&self.steps).unwrap_or_default()
+    }
+}
+
+/// Restores the saved scripts.
+pub fn load_scripts() -> Vec<Script> {
+    let restored = StorageService::new(Area::Local)
+        .ok()
+        .map(|storage| storage.restore(KEY));
+    match restored {
+        Some(Json(Ok(scripts))) => scripts,
+        _ => Vec::new(),
+    }
+}
+
+pub fn save_scripts(scripts: &[Script]) {
+    match StorageService::new(Area::Local) {
+        Ok(mut storage) => storage.store(KEY, Json(&scripts)),
+        Err(error) => log::error!("Failed to save the scripts: {}", error),
+    }
+}
+
+/// What a running script does next.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RunAction {
+    Send(wire::Message),
+    Wait(u32),
+    WaitFor(String),
+    Done,
+}
+
+/// The progress of a script being run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptRun {
+    pub name: String,
+    steps: Vec<ScriptStep>,
+    /// The index of the step being run.
+    pub position: usize,
+    /// The type of message the run waits for, if any.
+    pub waiting_for: Option<String>,
+    pub paused: bool,
+}
+
+impl ScriptRun {
+    pub fn new(script: &Script) -> Self {
+        ScriptRun {
+            name: script.name.clone(),
+            steps: script.steps.clone(),
+            position: 0,
+            waiting_for: None,
+            paused: false,
+        }
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Starts the current step and moves past it. Delays are finished by
+    /// calling `advance` again once elapsed, waits by `received`.
+    pub fn advance(&mut self) -> RunAction {
+        let step = match self.steps.get(self.position) {
+            Some(step) => step.clone(),
+            None => return RunAction::Done,
+        };
+        self.position += 1;
+        match step {
+            ScriptStep::Send(message) => RunAction::Send(message),
+            ScriptStep::Delay(millis) => RunAction::Wait(millis),
+            ScriptStep::WaitFor(r#type) => {
+                self.waiting_for = Some(r#type.clone());
+                RunAction::WaitFor(r#type)
+            }
+        }
+    }
+
+    /// Tells the run that a message was received, returning whether it was
+    /// the one it waited for.
+    pub fn received(&mut self, message: &wire::Message) -> bool {
+        match &self.waiting_for {
+            Some(r#type) if *r#type == message.type_tag() => {
+                self.waiting_for = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_steps() {
+        let steps = Script::parse_steps(
+            r#"[{"send":{"type":"START_GAME"}},{"delay":500},{"wait_for":"GAME_STARTED"}]"#,
+        );
+        assert_eq!(
+            steps,
+            Ok(vec![
+                ScriptStep::Send(wire::Message::StartGame),
+                ScriptStep::Delay(500),
+                ScriptStep::WaitFor("GAME_STARTED".to_string()),
+            ])
+        );
+        assert_eq!(
+            Script::parse_steps(r#"[{"send":{"type":"START"}}]"#),
+            Err("Step 1: unknown message type \"START\"".to_string())
+        );
+        assert!(Script::parse_steps(r#"[{"sleep":1}]"#).is_err());
+    }
+
+    #[test]
+    fn runs_scripts() {
+        let script = Script {
+            name: "start".to_string(),
+            steps: vec![
+                ScriptStep::Send(wire::Message::StartGame),
+                ScriptStep::WaitFor("GAME_STARTED".to_string()),
+                ScriptStep::Delay(10),
+            ],
+        };
+        let mut run = ScriptRun::new(&script);
+
+        assert_eq!(run.advance(), RunAction::Send(wire::Message::StartGame));
+        assert_eq!(
+            run.advance(),
+            RunAction::WaitFor("GAME_STARTED".to_string())
+        );
+        assert!(!run.received(&wire::Message::Pong));
+        assert!(run.received(&wire::Message::GameStarted));
+        assert!(!run.received(&wire::Message::GameStarted));
+        assert_eq!(run.advance(), RunAction::Wait(10));
+        assert_eq!(run.advance(), RunAction::Done);
+        assert_eq!(run.position, run.step_count());
+    }
+}
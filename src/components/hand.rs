@@ -0,0 +1,332 @@
+use yew::prelude::*;
+use yewtil::{Pure, PureComponent};
+
+use crate::game::faces::{Face, Rank, Suit};
+use crate::game::GameState;
+use crate::wire::{self, ComponentId};
+
+/// Angle between the first and the last card of a hand, at most.
+const MAX_FAN_DEGREES: f64 = 30.0;
+const FAN_DEGREES_PER_CARD: f64 = 4.0;
+/// How much lower the cards on the sides of the fan are, at most.
+const MAX_FAN_DROP_PX: f64 = 16.0;
+
+/// Order of the cards of a hand on screen. It only changes how the player sees
+/// the hand, the server keeps its own order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HandSort {
+    /// The order of the server.
+    Server,
+    BySuit,
+    ByValue,
+    /// The order the player chose by dragging the cards around.
+    Custom(Vec<ComponentId>),
+}
+
+impl Default for HandSort {
+    fn default() -> Self {
+        HandSort::Server
+    }
+}
+
+impl HandSort {
+    /// Returns the cards of the hand in this order.
+    pub fn apply(&self, hand: &wire::Hand, state: &GameState) -> Vec<ComponentId> {
+        let mut cards = hand.cards.clone();
+        match self {
+            HandSort::Server => {}
+            // Sorts are stable, so that the cards which can't be compared stay
+            // in the order of the server.
+            HandSort::BySuit => cards.sort_by_cached_key(|id| {
+                let state = card_state(state, id);
+                (suit_key(state), value_key(state))
+            }),
+            HandSort::ByValue => cards.sort_by_cached_key(|id| {
+                let state = card_state(state, id);
+                (value_key(state), suit_key(state))
+            }),
+            HandSort::Custom(order) => {
+                // Cards received since the player sorted the hand go last.
+                cards.sort_by_key(|id| {
+                    order
+                        .iter()
+                        .position(|ordered| ordered == id)
+                        .unwrap_or(order.len())
+                });
+            }
+        }
+        cards
+    }
+
+    /// Returns the order after moving a card to the place of another one.
+    pub fn moved(cards: &[ComponentId], card: &ComponentId, to: &ComponentId) -> HandSort {
+        let mut cards = cards.to_vec();
+        if let (Some(from), Some(to)) = (
+            cards.iter().position(|id| id == card),
+            cards.iter().position(|id| id == to),
+        ) {
+            let card = cards.remove(from);
+            cards.insert(to, card);
+        }
+        HandSort::Custom(cards)
+    }
+}
+
+fn card_state<'a>(state: &'a GameState, id: &ComponentId) -> Option<&'a wire::CardState> {
+    match state.components.get(id) {
+        Some(wire::Component::Card(card)) if card.has_identity() => Some(&card.state),
+        _ => None,
+    }
+}
+
+/// Cards without a suit, e.g. hidden ones, go last.
+fn suit_key(state: Option<&wire::CardState>) -> (u8, String) {
+    let suit = match state.and_then(|state| state.suit.as_deref()) {
+        Some(suit) => suit,
+        None => return (u8::MAX, String::new()),
+    };
+    match Suit::parse(suit) {
+        Some(Suit::Spades) => (0, String::new()),
+        Some(Suit::Hearts) => (1, String::new()),
+        Some(Suit::Diamonds) => (2, String::new()),
+        Some(Suit::Clubs) => (3, String::new()),
+        None => (4, suit.to_string()),
+    }
+}
+
+/// Aces are high, numbers come before the values which aren't.
+fn value_key(state: Option<&wire::CardState>) -> (u32, String) {
+    let state = match state {
+        Some(state) => state,
+        None => return (u32::MAX, String::new()),
+    };
+    if let Some(Face::Standard { rank, .. }) = Face::from_state(state) {
+        let order = match rank {
+            Rank::Number(number) => number.into(),
+            Rank::Jack => 11,
+            Rank::Queen => 12,
+            Rank::King => 13,
+            Rank::Ace => 14,
+        };
+        return (order, String::new());
+    }
+    match state.value.as_deref() {
+        Some(value) => match value.trim().parse() {
+            Ok(number) => (number, String::new()),
+            Err(_) => (u32::MAX - 1, value.to_string()),
+        },
+        None => (u32::MAX, String::new()),
+    }
+}
+
+/// Rotation in degrees and drop in pixels of a card in a fan.
+fn fan(index: usize, count: usize) -> (f64, f64) {
+    if count < 2 {
+        return (0.0, 0.0);
+    }
+    let spread = MAX_FAN_DEGREES.min(FAN_DEGREES_PER_CARD * (count - 1) as f64);
+    // From -1 for the first card to 1 for the last one.
+    let position = 2.0 * index as f64 / (count - 1) as f64 - 1.0;
+    let rotation = position * spread / 2.0;
+    let drop = position * position * MAX_FAN_DROP_PX * spread / MAX_FAN_DEGREES;
+    (rotation, drop)
+}
+
+/// Renders the cards of a hand, given as children in the order of `cards`,
+/// fanned out in an arc.
+#[derive(Clone, Properties, PartialEq)]
+pub struct PureHandView {
+    pub cards: Vec<ComponentId>,
+    pub children: Children,
+    pub sort: HandSort,
+    pub on_sort: Callback<HandSort>,
+    #[prop_or_default]
+    pub selected: Vec<ComponentId>,
+    /// How many cards can be selected, if the hand has cards awaited to be
+    /// selected.
+    #[prop_or_default]
+    pub selection: Option<(usize, usize)>,
+    #[prop_or_default]
+    pub on_confirm: Callback<()>,
+}
+pub type HandView = Pure<PureHandView>;
+
+impl PureComponent for PureHandView {
+    fn render(&self) -> Html {
+        let count = self.cards.len();
+        html! {
+            <div class="game-hand">
+                <div class="game-hand-cards">
+                    {
+                        for self
+                            .children
+                            .iter()
+                            .zip(self.cards.iter())
+                            .enumerate()
+                            .map(|(index, (card, id))| self.render_slot(index, count, id, card))
+                    }
+                </div>
+                { self.render_controls() }
+            </div>
+        }
+    }
+}
+
+impl PureHandView {
+    fn render_slot(&self, index: usize, count: usize, id: &ComponentId, card: Html) -> Html {
+        let (rotation, drop) = fan(index, count);
+        let mut style = format!(
+            "--fan-rotation: {:.2}deg; --fan-drop: {:.2}px;",
+            rotation, drop
+        );
+        // The cards overlap when there isn't enough room for all of them.
+        if index > 0 {
+            style.push_str(&format!(
+                " margin-left: min(.5rem, calc((100% - {} * var(--card-width)) / {}));",
+                count,
+                count - 1
+            ));
+        }
+        let selected = if self.selected.contains(id) {
+            "is-selected"
+        } else {
+            ""
+        };
+        html! {
+            <div class=("game-hand-slot", selected) style=style>{ card }</div>
+        }
+    }
+
+    fn render_controls(&self) -> Html {
+        let sort_button = |name: &str, sort: HandSort| {
+            let active = if self.sort == sort { "is-active" } else { "" };
+            let onclick = self.on_sort.reform(move |_: MouseEvent| sort.clone());
+            html! {
+                <button class=("button", active) onclick=onclick>{ name }</button>
+            }
+        };
+        let sort_buttons = if self.cards.len() > 1 {
+            html! {
+                <div class="buttons are-small has-addons">
+                    { sort_button("Dealt", HandSort::Server) }
+                    { sort_button("Suit", HandSort::BySuit) }
+                    { sort_button("Value", HandSort::ByValue) }
+                </div>
+            }
+        } else {
+            html! {}
+        };
+
+        let confirm = match self.selection {
+            Some((min, max)) => {
+                let selected = self.selected.len();
+                let hint = if min == max {
+                    format!("Select {} card(s)", min)
+                } else {
+                    format!("Select {} to {} cards", min, max)
+                };
+                html! {
+                    <button
+                        class="button is-small is-primary"
+                        disabled=selected < min || selected > max
+                        onclick=self.on_confirm.reform(|_| ())
+                        title=hint
+                        >
+                        { format!("Confirm ({})", selected) }
+                    </button>
+                }
+            }
+            None => html! {},
+        };
+
+        html! {
+            <div class="game-hand-controls">
+                { sort_buttons }
+                { confirm }
+            </div>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(cards: &[(&str, &str, &str)]) -> (GameState, wire::Hand) {
+        let mut state = GameState::default();
+        for (id, suit, value) in cards {
+            let card = wire::Card {
+                name: String::new(),
+                description: String::new(),
+                front_image: None,
+                back_image: None,
+                state: wire::CardState {
+                    suit: Some(suit.to_string()),
+                    value: Some(value.to_string()),
+                    ..Default::default()
+                },
+                owner: None,
+                visibility: wire::Visibility::Everyone,
+            };
+            state
+                .components
+                .insert((*id).into(), wire::Component::Card(card));
+        }
+        let hand = wire::Hand {
+            cards: cards.iter().map(|(id, _, _)| (*id).into()).collect(),
+            owner: None,
+            visibility: wire::Visibility::Everyone,
+        };
+        (state, hand)
+    }
+
+    fn ids(ids: &[&str]) -> Vec<ComponentId> {
+        ids.iter().map(|id| (*id).into()).collect()
+    }
+
+    #[test]
+    fn sorts_cards() {
+        let (state, hand) = state(&[
+            ("c1", "H", "2"),
+            ("c2", "S", "10"),
+            ("c3", "H", "1"),
+            ("c4", "S", "K"),
+        ]);
+
+        assert_eq!(
+            HandSort::Server.apply(&hand, &state),
+            ids(&["c1", "c2", "c3", "c4"])
+        );
+        assert_eq!(
+            HandSort::BySuit.apply(&hand, &state),
+            ids(&["c2", "c4", "c1", "c3"])
+        );
+        assert_eq!(
+            HandSort::ByValue.apply(&hand, &state),
+            ids(&["c1", "c2", "c4", "c3"])
+        );
+    }
+
+    #[test]
+    fn custom_order_keeps_new_cards_last() {
+        let (state, hand) = state(&[("c1", "H", "K"), ("c2", "S", "10"), ("c3", "H", "1")]);
+
+        let sort = HandSort::moved(&ids(&["c1", "c2"]), &"c1".into(), &"c2".into());
+        assert_eq!(sort, HandSort::Custom(ids(&["c2", "c1"])));
+        assert_eq!(sort.apply(&hand, &state), ids(&["c2", "c1", "c3"]));
+
+        let sort = HandSort::moved(&ids(&["c1", "c2", "c3"]), &"c3".into(), &"c1".into());
+        assert_eq!(sort.apply(&hand, &state), ids(&["c3", "c1", "c2"]));
+    }
+
+    #[test]
+    fn fans_symmetrically() {
+        assert_eq!(fan(0, 1), (0.0, 0.0));
+        let (first, first_drop) = fan(0, 5);
+        let (last, last_drop) = fan(4, 5);
+        assert_eq!(first, -last);
+        assert_eq!(first_drop, last_drop);
+        assert_eq!(fan(2, 5), (0.0, 0.0));
+        assert!(fan(0, 52).0 >= -MAX_FAN_DEGREES / 2.0);
+    }
+}
@@ -0,0 +1,197 @@
+//! Keyboard controls of the game table.
+//!
+//! Keys are identified by the `key` property of the keyboard events, e.g.
+//! `ArrowLeft` or `c`, so that the bindings follow the layout of the keyboard.
+//! The digits always pick one of the current options and can't be rebound.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyAction {
+    Previous,
+    Next,
+    PreviousZone,
+    NextZone,
+    /// Performs the awaited action on the focused component: clicks it,
+    /// selects it, picks it up or drops the picked up card onto it.
+    Activate,
+    /// Sends the selected cards.
+    Confirm,
+    /// Puts back the picked up card, or clears the selection.
+    Cancel,
+    Help,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 8] = [
+        KeyAction::Previous,
+        KeyAction::Next,
+        KeyAction::PreviousZone,
+        KeyAction::NextZone,
+        KeyAction::Activate,
+        KeyAction::Confirm,
+        KeyAction::Cancel,
+        KeyAction::Help,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            KeyAction::Previous => "Focus the previous card or component",
+            KeyAction::Next => "Focus the next card or component",
+            KeyAction::PreviousZone => "Focus the previous zone of the table",
+            KeyAction::NextZone => "Focus the next zone of the table",
+            KeyAction::Activate => "Play, select, pick up or drop onto the focused card",
+            KeyAction::Confirm => "Send the selected cards",
+            KeyAction::Cancel => "Put the picked up card back or clear the selection",
+            KeyAction::Help => "Show or hide this help",
+        }
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            KeyAction::Previous => &["ArrowLeft"],
+            KeyAction::Next => &["ArrowRight"],
+            KeyAction::PreviousZone => &["ArrowUp"],
+            KeyAction::NextZone => &["ArrowDown"],
+            KeyAction::Activate => &["Enter", " "],
+            KeyAction::Confirm => &["c"],
+            KeyAction::Cancel => &["Escape"],
+            KeyAction::Help => &["?"],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KeyBindings(BTreeMap<KeyAction, Vec<String>>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings(
+            KeyAction::ALL
+                .iter()
+                .map(|action| (*action, Self::default_keys(*action)))
+                .collect(),
+        )
+    }
+}
+
+impl KeyBindings {
+    fn default_keys(action: KeyAction) -> Vec<String> {
+        action
+            .default_keys()
+            .iter()
+            .map(|key| key.to_string())
+            .collect()
+    }
+
+    /// Binds the actions added since the bindings were saved to their default
+    /// keys.
+    pub fn with_defaults(mut self) -> Self {
+        for action in KeyAction::ALL.iter() {
+            self.0
+                .entry(*action)
+                .or_insert_with(|| Self::default_keys(*action));
+        }
+        self
+    }
+
+    pub fn keys(&self, action: KeyAction) -> &[String] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Binds a key to an action, unbinding it from the other actions.
+    pub fn bind(&mut self, action: KeyAction, key: &str) {
+        if is_digit(key) {
+            return;
+        }
+        for keys in self.0.values_mut() {
+            keys.retain(|bound| bound != key);
+        }
+        self.0.entry(action).or_default().push(key.to_string());
+    }
+
+    pub fn unbind(&mut self, action: KeyAction, key: &str) {
+        if let Some(keys) = self.0.get_mut(&action) {
+            keys.retain(|bound| bound != key);
+        }
+    }
+
+    pub fn action_for(&self, key: &str) -> Option<KeyAction> {
+        self.0
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|bound| bound == key))
+            .map(|(action, _)| *action)
+    }
+}
+
+/// Returns the index of the option picked by a digit key, `1` being the first
+/// one.
+pub fn option_index(key: &str) -> Option<usize> {
+    match key.parse::<usize>() {
+        Ok(digit @ 1..=9) if is_digit(key) => Some(digit - 1),
+        _ => None,
+    }
+}
+
+fn is_digit(key: &str) -> bool {
+    key.len() == 1 && key.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Name of a key, as displayed to the player.
+pub fn key_name(key: &str) -> &str {
+    match key {
+        " " => "Space",
+        "ArrowLeft" => "←",
+        "ArrowRight" => "→",
+        "ArrowUp" => "↑",
+        "ArrowDown" => "↓",
+        "Escape" => "Esc",
+        key => key,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.action_for(" "), Some(KeyAction::Activate));
+        assert_eq!(bindings.action_for("Escape"), Some(KeyAction::Cancel));
+        assert_eq!(bindings.action_for("x"), None);
+    }
+
+    #[test]
+    fn rebinding_moves_the_key() {
+        let mut bindings = KeyBindings::default();
+        bindings.bind(KeyAction::Confirm, "Enter");
+        assert_eq!(bindings.action_for("Enter"), Some(KeyAction::Confirm));
+        assert_eq!(bindings.keys(KeyAction::Activate), &[" ".to_string()]);
+
+        bindings.unbind(KeyAction::Confirm, "c");
+        assert_eq!(bindings.action_for("c"), None);
+
+        bindings.bind(KeyAction::Help, "1");
+        assert_eq!(bindings.action_for("1"), None);
+    }
+
+    #[test]
+    fn missing_actions_get_their_default_keys() {
+        let bindings: KeyBindings = serde_json::from_str(r#"{"Help":["h"]}"#).unwrap();
+        let bindings = bindings.with_defaults();
+        assert_eq!(bindings.action_for("h"), Some(KeyAction::Help));
+        assert_eq!(bindings.action_for("?"), None);
+        assert_eq!(bindings.action_for("ArrowLeft"), Some(KeyAction::Previous));
+    }
+
+    #[test]
+    fn digits_pick_options() {
+        assert_eq!(option_index("1"), Some(0));
+        assert_eq!(option_index("9"), Some(8));
+        assert_eq!(option_index("0"), None);
+        assert_eq!(option_index("+1"), None);
+        assert_eq!(option_index("a"), None);
+    }
+}
@@ -1,9 +1,45 @@
+pub mod animations;
+pub mod announcer;
+pub mod card;
+pub mod card_faces;
+pub mod chat;
+#[cfg(feature = "debug-console")]
+pub mod debug_drawer;
+// Compiled in the tests too, to test them without the console.
+#[cfg(any(test, feature = "debug-console"))]
+#[cfg_attr(not(feature = "debug-console"), allow(dead_code))]
+pub mod debug_history;
+#[cfg(any(test, feature = "debug-console"))]
+#[cfg_attr(not(feature = "debug-console"), allow(dead_code))]
+pub mod debug_scripts;
+pub mod hand;
+pub mod keyboard;
 pub mod navbar;
 pub mod neq_assign;
 pub mod notifications;
+pub mod roster;
+#[cfg(any(test, feature = "debug-console"))]
+#[cfg_attr(not(feature = "debug-console"), allow(dead_code))]
+pub mod state_inspector;
+pub mod table;
+#[cfg(feature = "debug-console")]
+pub mod traffic_stats;
+#[cfg(feature = "debug-console")]
 pub mod ws_dbg_console;
 
+pub use announcer::Announcer;
+pub use card::CardView;
+pub use chat::Chat;
+#[cfg(feature = "debug-console")]
+pub use debug_drawer::DebugDrawer;
 pub use navbar::Navbar;
 pub use neq_assign::NeqAssign;
 pub use notifications::Notifications;
+pub use roster::Roster;
+#[cfg(feature = "debug-console")]
+pub use state_inspector::StateInspector;
+pub use table::Table;
+#[cfg(feature = "debug-console")]
+pub use traffic_stats::TrafficStatsView;
+#[cfg(feature = "debug-console")]
 pub use ws_dbg_console::{Msg as WebSocketDebugConsoleMsg, WebSocketDebugConsole};
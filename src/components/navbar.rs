@@ -62,6 +62,9 @@ impl Component for Navbar {
                     <NavLink classes="navbar-item" route=AppRoute::ListGames>
                         { "List games" }
                     </NavLink>
+                    <NavLink classes="navbar-item" route=AppRoute::Replay>
+                        { "Replay a game" }
+                    </NavLink>
 
                     <div class="navbar-start">
                         <div class="navbar-item has-dropdown is-hoverable">
@@ -90,9 +93,9 @@ impl Component for Navbar {
                                 <a class="button is-primary">
                                     <strong>{ "Share" }</strong>
                                 </a>
-                                <a class="button is-light" >
+                                <NavLink classes="button is-light" route=AppRoute::Settings>
                                     { "Settings" }
-                                </a>
+                                </NavLink>
                             </div>
                         </div>
                     </div>
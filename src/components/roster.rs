@@ -0,0 +1,248 @@
+//! The players of the game, their presence and whose turn it is.
+
+use std::rc::Rc;
+use std::time::Duration;
+use yew::prelude::*;
+use yew::services::interval::{IntervalService, IntervalTask};
+use yewtil::NeqAssign;
+
+use crate::game::{GameState, Player};
+
+pub struct Roster {
+    link: ComponentLink<Self>,
+    props: Props,
+
+    /// When the current turn started and the last tick of its countdown, in
+    /// milliseconds.
+    turn_started: f64,
+    now: f64,
+    _tick_task: Option<IntervalTask>,
+}
+
+#[derive(Properties, Clone, Debug, PartialEq)]
+pub struct Props {
+    pub state: Rc<GameState>,
+    pub player_id: String,
+    /// Lets the player remove the others from the round, when they created it.
+    #[prop_or_default]
+    pub on_kick: Option<Callback<String>>,
+}
+
+pub enum Msg {
+    Tick,
+}
+
+impl Component for Roster {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut roster = Roster {
+            link,
+            props,
+
+            turn_started: 0.0,
+            now: 0.0,
+            _tick_task: None,
+        };
+        roster.start_turn();
+        roster
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let turn_number = |state: &GameState| state.turn.as_ref().map(|turn| turn.number);
+        let new_turn = turn_number(&props.state) != turn_number(&self.props.state);
+        let changed = self.props.neq_assign(props);
+        if new_turn {
+            self.start_turn();
+        }
+        changed
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Tick => {
+                self.now = js_sys::Date::now();
+                if self.remaining_seconds() == Some(0) {
+                    self._tick_task = None;
+                }
+                true
+            }
+        }
+    }
+
+    fn view(&self) -> Html {
+        let players = &self.props.state.players;
+        let content = if players.is_empty() {
+            html! { <p class="has-text-grey">{ "Waiting for players…" }</p> }
+        } else {
+            html! {
+                <ul>
+                    { for players.iter().map(|player| self.render_player(player)) }
+                </ul>
+            }
+        };
+
+        html! {
+            <div class="card game-roster">
+                <header class="card-header">
+                    <p class="card-header-title">{ "Players" }</p>
+                </header>
+                <div class="card-content">{ content }</div>
+            </div>
+        }
+    }
+}
+
+impl Roster {
+    fn start_turn(&mut self) {
+        self.turn_started = js_sys::Date::now();
+        self.now = self.turn_started;
+        let has_time_limit = self
+            .props
+            .state
+            .turn
+            .as_ref()
+            .map_or(false, |turn| turn.time_limit.is_some());
+        self._tick_task = if has_time_limit {
+            Some(
+                IntervalService::new()
+                    .spawn(Duration::from_secs(1), self.link.callback(|_| Msg::Tick)),
+            )
+        } else {
+            None
+        };
+    }
+
+    /// Seconds left to the active player, if the turn has a time limit.
+    fn remaining_seconds(&self) -> Option<u32> {
+        let time_limit = self.props.state.turn.as_ref()?.time_limit?;
+        let elapsed = (self.now - self.turn_started) / 1000.0;
+        Some((f64::from(time_limit) - elapsed).ceil().max(0.0) as u32)
+    }
+
+    fn render_player(&self, player: &Player) -> Html {
+        let state = &self.props.state;
+        let is_active = state
+            .turn
+            .as_ref()
+            .map_or(false, |turn| turn.player_id == player.id);
+        let is_you = player.id == self.props.player_id;
+
+        let connection = if player.connected {
+            html! { <span class="tag is-success is-light">{ "Online" }</span> }
+        } else {
+            html! { <span class="tag is-danger is-light">{ "Offline" }</span> }
+        };
+        let cards = match state.card_count(&player.id) {
+            1 => "1 card".to_string(),
+            count => format!("{} cards", count),
+        };
+        let score = match player.score {
+            Some(score) => {
+                html! { <span class="tag is-info is-light">{ format!("{} pts", score) }</span> }
+            }
+            None => html! {},
+        };
+        let countdown = match self.remaining_seconds() {
+            Some(seconds) if is_active => {
+                let urgency = if seconds <= 10 {
+                    "is-danger"
+                } else {
+                    "is-warning"
+                };
+                html! {
+                    <span class=("tag", urgency) role="timer" aria-label="Time left">
+                        { format_countdown(seconds) }
+                    </span>
+                }
+            }
+            _ => html! {},
+        };
+        let kick = match &self.props.on_kick {
+            Some(on_kick) if !is_you => {
+                let player_id = player.id.clone();
+                html! {
+                    <button
+                        class="button is-small is-danger is-light"
+                        aria-label=format!("Remove {} from the round", player.name)
+                        onclick=on_kick.reform(move |_| player_id.clone())
+                        >
+                        { "Remove" }
+                    </button>
+                }
+            }
+            _ => html! {},
+        };
+
+        html! {
+            <li
+                class=(
+                    "game-roster-player",
+                    if is_active { "is-active" } else { "" },
+                    if player.connected { "" } else { "is-disconnected" },
+                )
+                aria-current=if is_active { "true" } else { "false" }
+                >
+                <span
+                    class="game-roster-avatar"
+                    style=format!("background-color: hsl({}, 55%, 45%)", avatar_hue(&player.name))
+                    aria-hidden="true"
+                    >
+                    { initials(&player.name) }
+                </span>
+                <span class="game-roster-name">
+                    { &player.name }
+                    { if is_you { " (you)" } else { "" } }
+                </span>
+                <span class="tags">
+                    { connection }
+                    <span class="tag is-light">{ cards }</span>
+                    { score }
+                    { countdown }
+                </span>
+                { kick }
+            </li>
+        }
+    }
+}
+
+/// Letters drawn on the avatar of a player.
+fn initials(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Color of the avatar of a player, which stays the same across games.
+fn avatar_hue(name: &str) -> u32 {
+    name.bytes().fold(0u32, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(u32::from(byte))
+    }) % 360
+}
+
+fn format_countdown(seconds: u32) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_avatars() {
+        assert_eq!(initials("toto"), "T");
+        assert_eq!(initials("jean-claude"), "JC");
+        assert_eq!(initials("__"), "");
+        assert_eq!(avatar_hue("toto"), avatar_hue("toto"));
+        assert!(avatar_hue("titi") < 360);
+    }
+
+    #[test]
+    fn formats_countdowns() {
+        assert_eq!(format_countdown(65), "1:05");
+        assert_eq!(format_countdown(9), "0:09");
+    }
+}
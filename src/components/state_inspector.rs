@@ -0,0 +1,346 @@
+//! A developer panel showing the state of the game kept by the game manager,
+//! to understand what the server sent.
+
+use std::collections::BTreeSet;
+use std::rc::Rc;
+use yew::prelude::*;
+use yewtil::NeqAssign;
+
+use crate::agents::game_mgr::*;
+use crate::agents::game_ws_mgr::WebSocketStatus;
+use crate::game::GameState;
+use crate::wire::{Component as GameComponent, ComponentId, ComponentPosition};
+
+pub struct StateInspector {
+    link: ComponentLink<Self>,
+    game_mgr_agent: Box<dyn Bridge<GameMgr>>,
+
+    ws_status: WebSocketStatus,
+    game: Rc<GameState>,
+    /// What the last update of the state changed.
+    changes: Changes,
+    highlighted: Option<ComponentId>,
+}
+
+pub enum Msg {
+    Highlight(ComponentId),
+    GameMgrMessage(GameMgrResponse),
+}
+
+impl Component for StateInspector {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut game_mgr_agent = GameMgr::bridge(link.callback(Msg::GameMgrMessage));
+        game_mgr_agent.send(GameMgrRequest::GetState);
+
+        StateInspector {
+            link,
+            game_mgr_agent,
+
+            ws_status: WebSocketStatus::NotConnected,
+            game: Rc::new(GameState::default()),
+            changes: Changes::default(),
+            highlighted: None,
+        }
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Highlight(id) => {
+                let id = if self.highlighted.as_ref() == Some(&id) {
+                    None
+                } else {
+                    Some(id)
+                };
+                self.game_mgr_agent.send(GameMgrRequest::Highlight(id));
+                false
+            }
+            Msg::GameMgrMessage(response) => match response {
+                GameMgrResponse::GameStateChanged(game) => {
+                    if game == self.game {
+                        return false;
+                    }
+                    self.changes = Changes::between(&self.game, &game);
+                    self.game = game;
+                    true
+                }
+                GameMgrResponse::WebSocketStatusChanged(status) => {
+                    self.ws_status.neq_assign(status)
+                }
+                GameMgrResponse::Highlighted(id) => self.highlighted.neq_assign(id),
+                _ => false,
+            },
+        }
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="state-inspector">
+                { self.render_summary() }
+                { self.render_awaited_actions() }
+                <h3 class="title is-size-6">{ "Components" }</h3>
+                { self.render_tree() }
+            </div>
+        }
+    }
+}
+
+impl StateInspector {
+    fn render_summary(&self) -> Html {
+        let (status_class, status) = match &self.ws_status {
+            WebSocketStatus::NotConnected => ("is-danger", "Not connected".to_string()),
+            WebSocketStatus::Pending(info) => (
+                "is-warning",
+                format!("Connecting to {} as {}", info.game_id, info.identity()),
+            ),
+            WebSocketStatus::Connected(info) => (
+                "is-success",
+                format!("Connected to {} as {}", info.game_id, info.identity()),
+            ),
+        };
+        let game = &self.game;
+        let phase = match (&game.winners, game.started) {
+            (Some(winners), _) => format!("finished, won by {}", winners.join(", ")),
+            (None, true) => "started".to_string(),
+            (None, false) => "not started".to_string(),
+        };
+        let turn = match &game.turn {
+            Some(turn) => format!("turn {} of {}", turn.number, turn.player_id),
+            None => "no turn".to_string(),
+        };
+        let players = game
+            .players
+            .iter()
+            .map(|player| {
+                let connection = if player.connected { "" } else { " (offline)" };
+                match player.score {
+                    Some(score) => format!("{}{}: {}", player.name, connection, score),
+                    None => format!("{}{}", player.name, connection),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        html! {
+            <div class="field is-grouped is-grouped-multiline">
+                <span class=("tag", status_class)>{ status }</span>
+                <span class=("tag", if self.changes.game { "is-warning" } else { "is-light" })>
+                    { format!("Game {}, {}", phase, turn) }
+                </span>
+                <span class=("tag", if self.changes.players { "is-warning" } else { "is-light" })>
+                    { format!("Players: {}", if players.is_empty() { "none" } else { &players }) }
+                </span>
+            </div>
+        }
+    }
+
+    fn render_awaited_actions(&self) -> Html {
+        let actions = &self.game.awaited_actions;
+        html! {
+            <div class=("state-inspector-actions", if self.changes.awaited_actions { "is-changed" } else { "" })>
+                <h3 class="title is-size-6">{ format!("Awaited actions ({})", actions.len()) }</h3>
+                {
+                    for actions.iter().map(|action| html! {
+                        <pre>{ serde_json::to_string(action).unwrap_or_default() }</pre>
+                    })
+                }
+            </div>
+        }
+    }
+
+    fn render_tree(&self) -> Html {
+        let game = &self.game;
+        // Cards are shown in their hands rather than at the top of the tree.
+        let in_hands = game
+            .components
+            .values()
+            .filter_map(|component| match component {
+                GameComponent::Hand(hand) => Some(hand.cards.iter()),
+                _ => None,
+            })
+            .flatten()
+            .collect::<BTreeSet<_>>();
+        let render_zone = |name: &str, ids: Vec<&ComponentId>| {
+            if ids.is_empty() {
+                return html! {};
+            }
+            html! {
+                <li>
+                    <span class="state-inspector-zone">{ name }</span>
+                    <ul>{ for ids.into_iter().map(|id| self.render_node(id)) }</ul>
+                </li>
+            }
+        };
+        let positioned = |position: ComponentPosition| {
+            game.layout
+                .iter()
+                .filter(|(_, p)| **p == position)
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>()
+        };
+        let unplaced = game
+            .components
+            .keys()
+            .filter(|id| !game.layout.contains_key(id) && !in_hands.contains(id))
+            .collect::<Vec<_>>();
+
+        html! {
+            <ul class="state-inspector-tree">
+                { render_zone("top", positioned(ComponentPosition::Top)) }
+                { render_zone("left", positioned(ComponentPosition::Left)) }
+                { render_zone("center", positioned(ComponentPosition::Center)) }
+                { render_zone("right", positioned(ComponentPosition::Right)) }
+                { render_zone("bottom", positioned(ComponentPosition::Bottom)) }
+                { render_zone("not placed", unplaced) }
+            </ul>
+        }
+    }
+
+    fn render_node(&self, id: &ComponentId) -> Html {
+        let component = self.game.components.get(id);
+        let kind = match component {
+            Some(GameComponent::Card(_)) => "Card".to_string(),
+            Some(GameComponent::Hand(_)) => "Hand".to_string(),
+            Some(GameComponent::Unknown { r#type, .. }) => format!("{} (unknown)", r#type),
+            None => "missing".to_string(),
+        };
+        let fields = component
+            .and_then(|component| serde_json::to_string_pretty(&fields_of(component)).ok())
+            .unwrap_or_default();
+        let children = match component {
+            Some(GameComponent::Hand(hand)) => html! {
+                <ul>{ for hand.cards.iter().map(|card| self.render_node(card)) }</ul>
+            },
+            _ => html! {},
+        };
+        let is_highlighted = self.highlighted.as_ref() == Some(id);
+        let onclick = {
+            let id = id.clone();
+            self.link.callback(move |_| Msg::Highlight(id.clone()))
+        };
+
+        html! {
+            <li class=(
+                if self.changes.components.contains(id) { "is-changed" } else { "" },
+                if is_highlighted { "is-highlighted" } else { "" },
+            )>
+                <details>
+                    <summary>
+                        <button
+                            class="button is-small is-text"
+                            aria-pressed=if is_highlighted { "true" } else { "false" }
+                            title="Highlight on the table"
+                            onclick=onclick
+                            >
+                            { id.as_str() }
+                        </button>
+                        <span class="tag is-light">{ kind }</span>
+                    </summary>
+                    <pre>{ fields }</pre>
+                </details>
+                { children }
+            </li>
+        }
+    }
+}
+
+/// The fields of a component, without the tag already shown as its type.
+fn fields_of(component: &GameComponent) -> serde_json::Value {
+    let mut value = serde_json::to_value(component).unwrap_or_default();
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("type");
+    }
+    value
+}
+
+/// What changed in the state of the game with an update.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Changes {
+    /// The components added, modified or moved.
+    pub components: BTreeSet<ComponentId>,
+    pub awaited_actions: bool,
+    pub players: bool,
+    /// Whether the game started or finished, or the turn changed.
+    pub game: bool,
+}
+
+impl Changes {
+    pub fn between(before: &GameState, after: &GameState) -> Self {
+        let components = after
+            .components
+            .iter()
+            .filter(|(id, component)| before.components.get(id) != Some(component))
+            .map(|(id, _)| id)
+            .chain(
+                after
+                    .layout
+                    .iter()
+                    .filter(|(id, position)| before.layout.get(id) != Some(position))
+                    .map(|(id, _)| id),
+            )
+            .cloned()
+            .collect();
+
+        Changes {
+            components,
+            awaited_actions: before.awaited_actions != after.awaited_actions,
+            players: before.players != after.players,
+            game: before.started != after.started
+                || before.winners != after.winners
+                || before.turn != after.turn,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire;
+
+    #[test]
+    fn finds_the_changes() {
+        let mut before = GameState::default();
+        before.apply(
+            &wire::ComponentsUpdates {
+                updates: vec![wire::ComponentUpdate::Create {
+                    id: "hand".into(),
+                    component: wire::Hand {
+                        cards: vec![],
+                        owner: None,
+                        visibility: wire::Visibility::Everyone,
+                    }
+                    .into(),
+                }],
+            }
+            .into(),
+        );
+        let mut after = before.clone();
+        after.apply(
+            &wire::InterfaceUpdate {
+                components: vec![wire::InterfaceComponent {
+                    id: "hand".into(),
+                    position: ComponentPosition::Bottom,
+                }],
+            }
+            .into(),
+        );
+        after.apply(&wire::Message::GameStarted);
+
+        let changes = Changes::between(&before, &after);
+        assert_eq!(
+            changes.components.into_iter().collect::<Vec<_>>(),
+            vec![ComponentId::from("hand")]
+        );
+        assert!(changes.game);
+        assert!(!changes.players);
+        assert!(!changes.awaited_actions);
+
+        assert_eq!(Changes::between(&after, &after), Changes::default());
+    }
+}
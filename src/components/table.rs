@@ -0,0 +1,1049 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen::JsCast;
+use yew::prelude::*;
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use yewtil::NeqAssign;
+
+use crate::components::animations::{self, Animations, Positions};
+use crate::components::card_faces::BackTheme;
+use crate::components::hand::{HandSort, HandView};
+use crate::components::keyboard::{self, key_name, KeyAction, KeyBindings};
+use crate::components::CardView;
+use crate::game::aria;
+use crate::game::GameState;
+use crate::wire::{
+    self, AwaitedAction, Component as GameComponent, ComponentId, ComponentPosition, PerformAction,
+};
+
+/// The zones of the table, in the order they are rendered.
+const POSITIONS: [ComponentPosition; 5] = [
+    ComponentPosition::Top,
+    ComponentPosition::Left,
+    ComponentPosition::Center,
+    ComponentPosition::Right,
+    ComponentPosition::Bottom,
+];
+
+/// Attribute holding the ID of the rendered components, to find out what is
+/// under the pointer.
+pub const COMPONENT_ID_ATTRIBUTE: &str = "data-component-id";
+
+/// Distance in pixels the pointer has to travel before a card is dragged, so
+/// that a click isn't taken for a drag.
+const DRAG_THRESHOLD: i32 = 5;
+
+/// Must match the transition of `.game-drag-preview.is-snapping-back`.
+const SNAP_BACK_DURATION: Duration = Duration::from_millis(200);
+
+pub struct Table {
+    link: ComponentLink<Self>,
+    props: Props,
+    root: NodeRef,
+
+    /// Positions of the components before the state changed, and the cards
+    /// which were flipped, to be animated once the new state is rendered.
+    positions: Option<Positions>,
+    flipped: HashSet<ComponentId>,
+
+    drag: Option<Drag>,
+    _snap_back_task: Option<TimeoutTask>,
+
+    /// How the player sorted each hand.
+    hand_sorts: HashMap<ComponentId, HandSort>,
+    /// Cards selected for an awaited `SelectMany` action.
+    selected: Vec<ComponentId>,
+
+    /// Component focused with the keyboard, the only one reachable with Tab.
+    focused: Option<ComponentId>,
+    /// Whether the browser focus must be moved to the focused component once
+    /// rendered.
+    focus_pending: bool,
+    /// Card picked up with the keyboard, to be dropped onto the focused
+    /// component.
+    carrying: Option<ComponentId>,
+    show_help: bool,
+}
+
+#[derive(Properties, Clone, Debug, PartialEq)]
+pub struct Props {
+    pub state: Rc<GameState>,
+    pub player_id: String,
+    #[prop_or_default]
+    pub back_theme: BackTheme,
+    #[prop_or_default]
+    pub animations: Animations,
+    #[prop_or_default]
+    pub key_bindings: KeyBindings,
+    /// Called when the player performs one of the awaited actions.
+    #[prop_or_default]
+    pub on_action: Callback<PerformAction>,
+    /// A component to point out, e.g. from the developer tools.
+    #[prop_or_default]
+    pub highlighted: Option<ComponentId>,
+}
+
+/// A card being pressed, and dragged once the pointer moved. Coordinates are
+/// relative to the viewport.
+#[derive(Debug)]
+struct Drag {
+    card: ComponentId,
+    /// Cards which can only be selected are not dragged.
+    movable: bool,
+    pointer_id: i32,
+    origin: (i32, i32),
+    position: (i32, i32),
+    /// Where the card was grabbed, relative to its top left corner.
+    grab_offset: (i32, i32),
+    started: bool,
+    over: Option<ComponentId>,
+    snapping_back: bool,
+}
+
+/// An option picked with the digit keys.
+#[derive(Clone, Debug)]
+enum Choice {
+    Perform(PerformAction),
+    Select(ComponentId),
+}
+
+#[derive(Debug)]
+pub enum Msg {
+    PointerDown(ComponentId, PointerEvent),
+    PointerMove(PointerEvent),
+    PointerUp(PointerEvent),
+    PointerCancel,
+    SnappedBack,
+    SortHand(ComponentId, HandSort),
+    ConfirmSelection,
+    KeyDown(KeyboardEvent),
+    Focused(ComponentId),
+    ToggleHelp,
+}
+
+impl Component for Table {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Table {
+            link,
+            props,
+            root: NodeRef::default(),
+
+            positions: None,
+            flipped: HashSet::new(),
+
+            drag: None,
+            _snap_back_task: None,
+
+            hand_sorts: HashMap::new(),
+            selected: Vec::new(),
+
+            focused: None,
+            focus_pending: false,
+            carrying: None,
+            show_help: false,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if props.state != self.props.state && props.animations.is_enabled() {
+            self.prepare_animations(&props);
+        }
+        let new_turn = props.state.awaited_actions != self.props.state.awaited_actions
+            && !props.state.awaited_actions.is_empty();
+        let changed = self.props.neq_assign(props);
+        if new_turn {
+            self.focus_awaited_action();
+        }
+        // The dragged, picked up and selected cards may not be playable
+        // anymore.
+        if changed {
+            if let Some(drag) = &self.drag {
+                if !self.is_movable(&drag.card) {
+                    self.drag = None;
+                }
+            }
+            let state = &self.props.state;
+            if let Some(card) = &self.carrying {
+                if !state.is_draggable(card) {
+                    self.carrying = None;
+                }
+            }
+            self.selected
+                .retain(|card| state.selection_action(card).is_some());
+        }
+        changed
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::PointerDown(card, event) => {
+                if self.drag.is_some() || event.button() != 0 {
+                    return false;
+                }
+                let movable = self.is_movable(&card);
+                let state = &self.props.state;
+                if !movable
+                    && state.click_action(&card).is_none()
+                    && state.selection_action(&card).is_none()
+                {
+                    return false;
+                }
+                // Keeps the browser from dragging the image of the card, and
+                // sends the following events to the table even if the pointer
+                // leaves it.
+                event.prevent_default();
+                if let Some(target) = event
+                    .target()
+                    .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                {
+                    let _ = target.set_pointer_capture(event.pointer_id());
+                }
+
+                let position = (event.client_x(), event.client_y());
+                self.drag = Some(Drag {
+                    card,
+                    movable,
+                    pointer_id: event.pointer_id(),
+                    origin: position,
+                    position,
+                    grab_offset: (event.offset_x(), event.offset_y()),
+                    started: false,
+                    over: None,
+                    snapping_back: false,
+                });
+                false
+            }
+            Msg::PointerMove(event) => {
+                let position = (event.client_x(), event.client_y());
+                let over = match &self.drag {
+                    Some(drag) if drag.pointer_id == event.pointer_id() && !drag.snapping_back => {
+                        self.drop_target_at(position, &drag.card)
+                    }
+                    _ => return false,
+                };
+
+                let drag = self.drag.as_mut().unwrap();
+                drag.position = position;
+                if !drag.started && drag.movable {
+                    let (dx, dy) = (position.0 - drag.origin.0, position.1 - drag.origin.1);
+                    drag.started = dx.abs() > DRAG_THRESHOLD || dy.abs() > DRAG_THRESHOLD;
+                }
+                drag.over = over;
+                drag.started
+            }
+            Msg::PointerUp(event) => {
+                let drag = match &self.drag {
+                    Some(drag) if drag.pointer_id == event.pointer_id() && !drag.snapping_back => {
+                        drag
+                    }
+                    _ => return false,
+                };
+                let card = drag.card.clone();
+                if !drag.started {
+                    // The card was clicked.
+                    self.drag = None;
+                    return self.activate(card);
+                }
+
+                let position = (event.client_x(), event.client_y());
+                let action = self
+                    .drop_target_at(position, &card)
+                    .and_then(|target| self.props.state.drop_action(&card, &target))
+                    .cloned();
+                if let Some(action) = action {
+                    self.props.on_action.emit(action.into());
+                    self.drag = None;
+                } else if let Some(target) = self.hand_neighbour_at(position, &card) {
+                    self.move_in_hand(&card, &target);
+                    self.drag = None;
+                } else {
+                    self.snap_back();
+                }
+                true
+            }
+            Msg::PointerCancel => match self.drag {
+                Some(_) => {
+                    self.snap_back();
+                    true
+                }
+                None => false,
+            },
+            Msg::SnappedBack => {
+                self.drag = None;
+                self._snap_back_task = None;
+                true
+            }
+            Msg::SortHand(hand, sort) => {
+                self.hand_sorts.insert(hand, sort);
+                true
+            }
+            Msg::ConfirmSelection => {
+                let action = self
+                    .props
+                    .state
+                    .confirmed_selection(&self.selected)
+                    .cloned();
+                if let Some(action) = action {
+                    let selected = std::mem::take(&mut self.selected);
+                    self.carrying = None;
+                    self.props.on_action.emit(PerformAction {
+                        action,
+                        selected_components: selected
+                            .iter()
+                            .map(|card| card.as_str().to_string())
+                            .collect(),
+                    });
+                }
+                true
+            }
+            Msg::KeyDown(event) => self.key_down(event),
+            Msg::Focused(id) => {
+                self.focus_pending = false;
+                self.focused.neq_assign(Some(id))
+            }
+            Msg::ToggleHelp => {
+                self.show_help = !self.show_help;
+                true
+            }
+        }
+    }
+
+    fn rendered(&mut self, _first_render: bool) {
+        if self.focus_pending {
+            self.focus_pending = false;
+            self.focus_element();
+        }
+
+        let positions = match self.positions.take() {
+            Some(positions) => positions,
+            None => return,
+        };
+        let root = match self.root.cast::<web_sys::Element>() {
+            Some(root) => root,
+            None => return,
+        };
+
+        let cards = self
+            .props
+            .state
+            .components
+            .iter()
+            .filter(|(_, component)| matches!(component, GameComponent::Card(_)))
+            .map(|(id, _)| id.clone())
+            .collect();
+        let flipped = std::mem::take(&mut self.flipped);
+        animations::play(&root, &positions, &cards, &flipped, &self.props.animations);
+    }
+
+    fn view(&self) -> Html {
+        let help_keys = self.props.key_bindings.keys(KeyAction::Help);
+        // The first component is reachable with Tab until another is focused.
+        let tab_stop = match &self.focused {
+            Some(focused) => Some(focused.clone()),
+            None => self.focus_order().into_iter().flatten().next(),
+        };
+        html! {
+            <>
+                <div
+                    class="game-table"
+                    role="region"
+                    aria-label="Game table"
+                    ref=self.root.clone()
+                    onpointermove=self.link.callback(Msg::PointerMove)
+                    onpointerup=self.link.callback(Msg::PointerUp)
+                    onpointercancel=self.link.callback(|_| Msg::PointerCancel)
+                    onkeydown=self.link.callback(Msg::KeyDown)
+                    >
+                    {
+                        for POSITIONS
+                            .iter()
+                            .map(|position| self.render_zone(*position, tab_stop.as_ref()))
+                    }
+                    { self.render_drag_preview() }
+                </div>
+                <p class="help game-table-help">
+                    <a onclick=self.link.callback(|_| Msg::ToggleHelp)>{ "Keyboard controls" }</a>
+                    {
+                        for help_keys.first().map(|key| html! {
+                            <>{ " (press " }<kbd>{ key_name(key) }</kbd>{ ")" }</>
+                        })
+                    }
+                </p>
+                { self.render_help() }
+            </>
+        }
+    }
+}
+
+impl Table {
+    /// Remembers what the table looks like before rendering a new state.
+    fn prepare_animations(&mut self, props: &Props) {
+        let root = match self.root.cast::<web_sys::Element>() {
+            Some(root) => root,
+            None => return,
+        };
+        self.positions = Some(animations::measure(&root));
+
+        let (before, after) = (&self.props.state, &props.state);
+        self.flipped = after
+            .components
+            .keys()
+            .filter(|id| before.components.contains_key(id))
+            .filter(|id| {
+                before.is_face_visible(id, &self.props.player_id)
+                    != after.is_face_visible(id, &props.player_id)
+            })
+            .cloned()
+            .collect();
+    }
+
+    /// Sends the dragged card back to where it was taken from.
+    fn snap_back(&mut self) {
+        if !self.props.animations.is_enabled() {
+            self.drag = None;
+            return;
+        }
+        if let Some(drag) = &mut self.drag {
+            drag.snapping_back = true;
+            drag.over = None;
+            drag.position = drag.origin;
+            self._snap_back_task = Some(
+                TimeoutService::new()
+                    .spawn(SNAP_BACK_DURATION, self.link.callback(|_| Msg::SnappedBack)),
+            );
+        }
+    }
+
+    fn key_down(&mut self, event: KeyboardEvent) -> ShouldRender {
+        if event.ctrl_key() || event.alt_key() || event.meta_key() {
+            return false;
+        }
+        // Leaves the keys to the controls of the table, e.g. the buttons
+        // sorting the hands.
+        let on_control = event
+            .target()
+            .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+            .map_or(false, |target| {
+                matches!(target.tag_name().as_str(), "BUTTON" | "INPUT" | "SELECT")
+            });
+        if on_control && !self.show_help {
+            return false;
+        }
+        let key = event.key();
+        if let Some(index) = keyboard::option_index(&key) {
+            event.prevent_default();
+            return match self.options().into_iter().nth(index) {
+                Some((_, choice)) => self.choose(choice),
+                None => false,
+            };
+        }
+        let action = match self.props.key_bindings.action_for(&key) {
+            Some(action) => action,
+            None => return false,
+        };
+        event.prevent_default();
+
+        if self.show_help {
+            // Only closing the help makes sense while it hides the table.
+            self.show_help = !matches!(action, KeyAction::Help | KeyAction::Cancel);
+            return !self.show_help;
+        }
+        match action {
+            KeyAction::Previous
+            | KeyAction::Next
+            | KeyAction::PreviousZone
+            | KeyAction::NextZone => self.move_focus(action),
+            KeyAction::Activate => match self.focused.clone() {
+                Some(id) => self.activate(id),
+                None => self.move_focus(action),
+            },
+            KeyAction::Confirm => self.update(Msg::ConfirmSelection),
+            KeyAction::Cancel => {
+                let changed = self.carrying.is_some() || !self.selected.is_empty();
+                self.carrying = None;
+                self.selected.clear();
+                changed
+            }
+            KeyAction::Help => {
+                self.show_help = true;
+                true
+            }
+        }
+    }
+
+    /// Components which can be focused with the keyboard, by zone, the cards
+    /// of the hands following them in the order they are displayed.
+    fn focus_order(&self) -> Vec<Vec<ComponentId>> {
+        let state = &self.props.state;
+        POSITIONS
+            .iter()
+            .map(|position| {
+                let mut zone = Vec::new();
+                for id in state.components_at(*position) {
+                    zone.push(id.clone());
+                    if let Some(GameComponent::Hand(hand)) = state.components.get(id) {
+                        zone.extend(self.sorted_hand(id, hand));
+                    }
+                }
+                zone
+            })
+            .filter(|zone| !zone.is_empty())
+            .collect()
+    }
+
+    fn move_focus(&mut self, action: KeyAction) -> ShouldRender {
+        let zones = self.focus_order();
+        if zones.is_empty() {
+            return false;
+        }
+        let current = self.focused.as_ref().and_then(|focused| {
+            zones.iter().enumerate().find_map(|(zone, ids)| {
+                ids.iter()
+                    .position(|id| id == focused)
+                    .map(|index| (zone, index))
+            })
+        });
+
+        let (zone, index) = match (current, action) {
+            (None, _) => (0, 0),
+            (Some((zone, index)), KeyAction::Previous) => {
+                let len = zones[zone].len();
+                (zone, (index + len - 1) % len)
+            }
+            (Some((zone, index)), KeyAction::Next) => (zone, (index + 1) % zones[zone].len()),
+            (Some((zone, _)), KeyAction::PreviousZone) => {
+                ((zone + zones.len() - 1) % zones.len(), 0)
+            }
+            (Some((zone, _)), _) => ((zone + 1) % zones.len(), 0),
+        };
+        self.focused = Some(zones[zone][index].clone());
+        self.focus_pending = true;
+        true
+    }
+
+    /// Focuses the first component an action is awaited on, unless the player
+    /// is busy outside of the table.
+    fn focus_awaited_action(&mut self) {
+        let state = &self.props.state;
+        let target = self.focus_order().into_iter().flatten().find(|id| {
+            state.click_action(id).is_some()
+                || state.selection_action(id).is_some()
+                || state.is_draggable(id)
+        });
+        if let (Some(target), true) = (target, self.may_take_focus()) {
+            self.focused = Some(target);
+            self.focus_pending = true;
+        }
+    }
+
+    /// Whether the focus is on the table or on nothing in particular.
+    fn may_take_focus(&self) -> bool {
+        let active = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.active_element());
+        let active = match active {
+            Some(active) if active.tag_name() != "BODY" => active,
+            _ => return true,
+        };
+        self.root
+            .cast::<web_sys::Node>()
+            .map_or(false, |root| root.contains(Some(&active)))
+    }
+
+    /// Gives the browser focus to the focused component.
+    fn focus_element(&self) {
+        let (root, focused) = match (self.root.cast::<web_sys::Element>(), &self.focused) {
+            (Some(root), Some(focused)) => (root, focused),
+            _ => return,
+        };
+        let selector = format!(
+            "[{}=\"{}\"]",
+            COMPONENT_ID_ATTRIBUTE,
+            focused.as_str().replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        if let Ok(Some(element)) = root.query_selector(&selector) {
+            if let Ok(element) = element.dyn_into::<web_sys::HtmlElement>() {
+                let _ = element.focus();
+            }
+        }
+    }
+
+    /// Performs the action awaited on a component when it is clicked or
+    /// activated with the keyboard.
+    fn activate(&mut self, id: ComponentId) -> ShouldRender {
+        let state = self.props.state.clone();
+        if let Some(card) = self.carrying.take() {
+            if let Some(action) = state.drop_action(&card, &id) {
+                self.props.on_action.emit(action.clone().into());
+            } else if card != id {
+                // Activating the picked up card again puts it back.
+                self.carrying = Some(card);
+                return false;
+            }
+            return true;
+        }
+
+        if let Some(action) = state.click_action(&id) {
+            self.props.on_action.emit(action.clone().into());
+            false
+        } else if state.selection_action(&id).is_some() {
+            self.toggle_selection(id)
+        } else if state.is_draggable(&id) {
+            self.carrying = Some(id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The options offered by the digit keys: the targets of the picked up
+    /// card, or else every awaited action.
+    fn options(&self) -> Vec<(String, Choice)> {
+        let state = &self.props.state;
+        if let Some(card) = &self.carrying {
+            return state
+                .drop_targets(card)
+                .filter_map(|target| {
+                    let action = state.drop_action(card, &target)?;
+                    Some((
+                        format!("Drop onto {}", self.label(&target)),
+                        Choice::Perform(action.clone().into()),
+                    ))
+                })
+                .collect();
+        }
+
+        let mut options = Vec::new();
+        for action in &state.awaited_actions {
+            match action {
+                AwaitedAction::OnClick { target_component } => options.push((
+                    format!("Click {}", self.label(&target_component.into())),
+                    Choice::Perform(action.clone().into()),
+                )),
+                AwaitedAction::OnDrop {
+                    source_component,
+                    target_component,
+                } => options.push((
+                    format!(
+                        "Move {} onto {}",
+                        self.label(&source_component.into()),
+                        self.label(&target_component.into())
+                    ),
+                    Choice::Perform(action.clone().into()),
+                )),
+                AwaitedAction::SelectMany {
+                    among_components, ..
+                } => options.extend(among_components.iter().map(|component| {
+                    let id = ComponentId::from(component);
+                    let verb = if self.selected.contains(&id) {
+                        "Unselect"
+                    } else {
+                        "Select"
+                    };
+                    (format!("{} {}", verb, self.label(&id)), Choice::Select(id))
+                })),
+                AwaitedAction::Unknown { .. } => {}
+            }
+        }
+        options
+    }
+
+    fn choose(&mut self, choice: Choice) -> ShouldRender {
+        match choice {
+            Choice::Perform(action) => {
+                self.carrying = None;
+                self.props.on_action.emit(action);
+                true
+            }
+            Choice::Select(id) => self.toggle_selection(id),
+        }
+    }
+
+    fn label(&self, id: &ComponentId) -> String {
+        aria::component_name(&self.props.state, id, &self.props.player_id)
+    }
+
+    /// Whether the card can be dragged, either to play it or to sort a hand.
+    fn is_movable(&self, card: &ComponentId) -> bool {
+        self.props.state.is_draggable(card) || self.is_sortable(card)
+    }
+
+    /// Whether the card is in a hand the player can sort, i.e. one of their
+    /// own hands or a shared one.
+    fn is_sortable(&self, card: &ComponentId) -> bool {
+        match self.props.state.hand_of(card) {
+            Some((_, hand)) => {
+                hand.cards.len() > 1
+                    && hand
+                        .owner
+                        .as_ref()
+                        .map_or(true, |owner| *owner == self.props.player_id)
+            }
+            None => false,
+        }
+    }
+
+    fn sorted_hand(&self, id: &ComponentId, hand: &wire::Hand) -> Vec<ComponentId> {
+        match self.hand_sorts.get(id) {
+            Some(sort) => sort.apply(hand, &self.props.state),
+            None => hand.cards.clone(),
+        }
+    }
+
+    /// Moves a card to the place of another card of its hand.
+    fn move_in_hand(&mut self, card: &ComponentId, to: &ComponentId) {
+        if let Some((id, hand)) = self.props.state.hand_of(card) {
+            let sort = HandSort::moved(&self.sorted_hand(id, hand), card, to);
+            self.hand_sorts.insert(id.clone(), sort);
+        }
+    }
+
+    /// Selects or unselects a card for the awaited `SelectMany` action.
+    fn toggle_selection(&mut self, card: ComponentId) -> ShouldRender {
+        let action = match self.props.state.selection_action(&card) {
+            Some(action) => action,
+            None => return false,
+        };
+        if let Some(index) = self.selected.iter().position(|selected| *selected == card) {
+            self.selected.remove(index);
+            return true;
+        }
+
+        // Only the cards of a single action can be selected at once.
+        let state = &self.props.state;
+        self.selected
+            .retain(|selected| state.selection_action(selected) == Some(action));
+        let max = match action {
+            AwaitedAction::SelectMany { max, .. } => *max,
+            _ => 0,
+        };
+        if self.selected.len() < max {
+            self.selected.push(card);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Finds the component under the given point that the dragged card can be
+    /// dropped onto.
+    fn drop_target_at(&self, position: (i32, i32), card: &ComponentId) -> Option<ComponentId> {
+        self.component_at(position, |id| {
+            self.props.state.drop_action(card, id).is_some()
+        })
+    }
+
+    /// Finds the card of the same hand as the dragged card under the given
+    /// point, if the hand can be sorted.
+    fn hand_neighbour_at(&self, position: (i32, i32), card: &ComponentId) -> Option<ComponentId> {
+        if !self.is_sortable(card) {
+            return None;
+        }
+        let (_, hand) = self.props.state.hand_of(card)?;
+        self.component_at(position, |id| id != card && hand.cards.contains(id))
+    }
+
+    /// Finds the first component under the given point accepted by the given
+    /// predicate, searching from the innermost element.
+    fn component_at(
+        &self,
+        (x, y): (i32, i32),
+        accept: impl Fn(&ComponentId) -> bool,
+    ) -> Option<ComponentId> {
+        let document = web_sys::window()?.document()?;
+        let mut element = document.element_from_point(x as f32, y as f32);
+        while let Some(current) = element {
+            if let Some(id) = current.get_attribute(COMPONENT_ID_ATTRIBUTE) {
+                let id = ComponentId::from(id);
+                if accept(&id) {
+                    return Some(id);
+                }
+            }
+            element = current.parent_element();
+        }
+        None
+    }
+
+    fn render_zone(&self, position: ComponentPosition, tab_stop: Option<&ComponentId>) -> Html {
+        let zone_class = match position {
+            ComponentPosition::Top => "game-zone-top",
+            ComponentPosition::Bottom => "game-zone-bottom",
+            ComponentPosition::Left => "game-zone-left",
+            ComponentPosition::Right => "game-zone-right",
+            ComponentPosition::Center => "game-zone-center",
+        };
+        let zone_name = match position {
+            ComponentPosition::Top => "Top of the table",
+            ComponentPosition::Bottom => "Bottom of the table",
+            ComponentPosition::Left => "Left of the table",
+            ComponentPosition::Right => "Right of the table",
+            ComponentPosition::Center => "Center of the table",
+        };
+        html! {
+            <div class=("game-zone", zone_class) role="group" aria-label=zone_name>
+                {
+                    for self
+                        .props
+                        .state
+                        .components_at(position)
+                        .map(|id| self.render_component(id, tab_stop))
+                }
+            </div>
+        }
+    }
+
+    /// Renders a component, which is reachable with Tab if it is the
+    /// `tab_stop`.
+    fn render_component(&self, id: &ComponentId, tab_stop: Option<&ComponentId>) -> Html {
+        let content = match self.props.state.components.get(id) {
+            Some(GameComponent::Card(card)) => {
+                let face_up = self.props.state.is_face_visible(id, &self.props.player_id);
+                html! {
+                    <CardView
+                        card=card.clone()
+                        face_up=face_up
+                        back_theme=self.props.back_theme.clone()
+                        />
+                }
+            }
+            Some(GameComponent::Hand(hand)) => self.render_hand(id, hand, tab_stop),
+            Some(GameComponent::Unknown { .. }) | None => html! {
+                <div class="game-component-missing">{ id.as_str() }</div>
+            },
+        };
+
+        let onpointerdown = {
+            let id = id.clone();
+            self.link
+                .callback(move |event: PointerEvent| Msg::PointerDown(id.clone(), event))
+        };
+        let onfocus = {
+            let id = id.clone();
+            self.link
+                .callback(move |_: FocusEvent| Msg::Focused(id.clone()))
+        };
+        let is_tab_stop = tab_stop == Some(id);
+        let state = &self.props.state;
+        let role = match state.components.get(id) {
+            Some(GameComponent::Hand(_)) => "group",
+            _ if state.click_action(id).is_some()
+                || state.selection_action(id).is_some()
+                || state.is_draggable(id) =>
+            {
+                "button"
+            }
+            _ => "img",
+        };
+        html! {
+            <div
+                class=(
+                    "game-component",
+                    self.drag_classes(id),
+                    if self.props.highlighted.as_ref() == Some(id) { "is-highlighted" } else { "" },
+                )
+                data-component-id=id.as_str()
+                role=role
+                aria-label=self.aria_label(id)
+                tabindex=if is_tab_stop { "0" } else { "-1" }
+                onpointerdown=onpointerdown
+                onfocus=onfocus
+                >
+                { content }
+            </div>
+        }
+    }
+
+    /// Accessible name of a component, followed by what the player can do
+    /// with it.
+    fn aria_label(&self, id: &ComponentId) -> String {
+        let state = &self.props.state;
+        let mut label = aria::component_label(state, id, &self.props.player_id);
+        let status = match &self.carrying {
+            Some(card) if card == id => Some("picked up"),
+            Some(card) if state.drop_action(card, id).is_some() => Some("can be dropped here"),
+            _ if self.selected.contains(id) => Some("selected"),
+            _ if state.selection_action(id).is_some() => Some("can be selected"),
+            _ if state.click_action(id).is_some() || state.is_draggable(id) => Some("playable"),
+            _ => None,
+        };
+        if let Some(status) = status {
+            label.push_str(", ");
+            label.push_str(status);
+        }
+        label
+    }
+
+    fn render_hand(
+        &self,
+        id: &ComponentId,
+        hand: &wire::Hand,
+        tab_stop: Option<&ComponentId>,
+    ) -> Html {
+        let cards = self.sorted_hand(id, hand);
+        let selection = hand
+            .cards
+            .iter()
+            .find_map(|card| self.props.state.selection_action(card))
+            .and_then(|action| match action {
+                AwaitedAction::SelectMany { min, max, .. } => Some((*min, *max)),
+                _ => None,
+            });
+        let on_sort = {
+            let id = id.clone();
+            self.link
+                .callback(move |sort| Msg::SortHand(id.clone(), sort))
+        };
+
+        html! {
+            <HandView
+                cards=cards.clone()
+                sort=self.hand_sorts.get(id).cloned().unwrap_or_default()
+                on_sort=on_sort
+                selected=self.selected.clone()
+                selection=selection
+                on_confirm=self.link.callback(|_| Msg::ConfirmSelection)
+                >
+                { for cards.iter().map(|card| self.render_component(card, tab_stop)) }
+            </HandView>
+        }
+    }
+
+    fn drag_classes(&self, id: &ComponentId) -> Vec<&'static str> {
+        let mut classes = Vec::new();
+        if let Some(card) = &self.carrying {
+            if card == id {
+                classes.push("is-carried");
+            } else if self.props.state.drop_action(card, id).is_some() {
+                classes.push("is-drop-target");
+                if self.focused.as_ref() == Some(id) {
+                    classes.push("is-drop-hover");
+                }
+            }
+            return classes;
+        }
+        match &self.drag {
+            Some(drag) if drag.started => {
+                if drag.card == *id {
+                    classes.push("is-dragged");
+                } else if self.props.state.drop_action(&drag.card, id).is_some() {
+                    classes.push("is-drop-target");
+                    if drag.over.as_ref() == Some(id) {
+                        classes.push("is-drop-hover");
+                    }
+                }
+            }
+            _ => {
+                if self.props.state.is_draggable(id) {
+                    classes.push("is-draggable");
+                } else if self.is_sortable(id) {
+                    classes.push("is-sortable");
+                }
+                if self.props.state.selection_action(id).is_some() {
+                    classes.push("is-selectable");
+                }
+            }
+        }
+        classes
+    }
+
+    fn render_drag_preview(&self) -> Html {
+        let drag = match &self.drag {
+            Some(drag) if drag.started => drag,
+            _ => return html! {},
+        };
+        let card = match self.props.state.components.get(&drag.card) {
+            Some(GameComponent::Card(card)) => card,
+            _ => return html! {},
+        };
+
+        let face_up = self
+            .props
+            .state
+            .is_face_visible(&drag.card, &self.props.player_id);
+        let style = format!(
+            "left: {}px; top: {}px",
+            drag.position.0 - drag.grab_offset.0,
+            drag.position.1 - drag.grab_offset.1
+        );
+        let snapping_back = if drag.snapping_back {
+            "is-snapping-back"
+        } else {
+            ""
+        };
+        html! {
+            <div class=("game-drag-preview", snapping_back) style=style aria-hidden="true">
+                <CardView
+                    card=card.clone()
+                    face_up=face_up
+                    back_theme=self.props.back_theme.clone()
+                    />
+            </div>
+        }
+    }
+
+    fn render_help(&self) -> Html {
+        if !self.show_help {
+            return html! {};
+        }
+        let close = self.link.callback(|_| Msg::ToggleHelp);
+        let render_binding = |action: &KeyAction| {
+            html! {
+                <tr>
+                    <td>
+                        {
+                            for self.props.key_bindings.keys(*action).iter().map(|key| html! {
+                                <kbd>{ key_name(key) }</kbd>
+                            })
+                        }
+                    </td>
+                    <td>{ action.description() }</td>
+                </tr>
+            }
+        };
+        let options = self.options();
+
+        html! {
+            <div class="modal is-active" onkeydown=self.link.callback(Msg::KeyDown)>
+                <div class="modal-background" onclick=close.clone()></div>
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ "Keyboard controls" }</p>
+                        <button class="delete" aria-label="Close" onclick=close></button>
+                    </header>
+                    <section class="modal-card-body">
+                        <table class="table is-fullwidth">
+                            <tbody>
+                                { for KeyAction::ALL.iter().map(render_binding) }
+                            </tbody>
+                        </table>
+                        <h3 class="title is-6">{ "Options" }</h3>
+                        {
+                            if options.is_empty() {
+                                html! { <p>{ "Nothing to do right now." }</p> }
+                            } else {
+                                html! {
+                                    <table class="table is-fullwidth">
+                                        <tbody>
+                                            {
+                                                for options.iter().enumerate().take(9).map(|(index, (label, _))| html! {
+                                                    <tr>
+                                                        <td><kbd>{ index + 1 }</kbd></td>
+                                                        <td>{ label }</td>
+                                                    </tr>
+                                                })
+                                            }
+                                        </tbody>
+                                    </table>
+                                }
+                            }
+                        }
+                    </section>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! A developer panel showing the statistics on the traffic of the connection
+//! to the game server, refreshed every second.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+use yew::prelude::*;
+use yew::services::interval::{IntervalService, IntervalTask};
+
+use crate::agents::game_ws_mgr::*;
+use crate::agents::ws_stats::{Counter, TrafficStats};
+
+pub struct TrafficStatsView {
+    link: ComponentLink<Self>,
+    ws_agent: Box<dyn Bridge<GameWsMgr>>,
+
+    stats: Option<TrafficStats>,
+    /// When the statistics were received, in milliseconds.
+    now: f64,
+    _refresh_task: IntervalTask,
+}
+
+pub enum Msg {
+    Refresh,
+    WebSocketMessage(GameWsResponse),
+}
+
+impl Component for TrafficStatsView {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut ws_agent = GameWsMgr::bridge(link.callback(Msg::WebSocketMessage));
+        ws_agent.send(GameWsRequest::GetStats);
+        let refresh_task =
+            IntervalService::new().spawn(Duration::from_secs(1), link.callback(|_| Msg::Refresh));
+
+        TrafficStatsView {
+            link,
+            ws_agent,
+
+            stats: None,
+            now: 0.0,
+            _refresh_task: refresh_task,
+        }
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::Refresh => {
+                self.ws_agent.send(GameWsRequest::GetStats);
+                false
+            }
+            Msg::WebSocketMessage(GameWsResponse::Stats(stats)) => {
+                self.stats = Some(stats);
+                self.now = js_sys::Date::now();
+                true
+            }
+            Msg::WebSocketMessage(_) => false,
+        }
+    }
+
+    fn view(&self) -> Html {
+        let stats = match &self.stats {
+            Some(stats) => stats,
+            None => return html! {},
+        };
+        let connection = match &stats.info {
+            Some(info) => format!("Game {} as {}", info.game_id, info.identity()),
+            None => "No connection yet".to_string(),
+        };
+
+        html! {
+            <div class="traffic-stats">
+                <div class="field is-grouped is-grouped-multiline">
+                    <span class="tag is-light">{ connection }</span>
+                    <span class=("tag", if stats.reconnects > 0 { "is-warning" } else { "is-light" })>
+                        { format!("Reconnects: {}", stats.reconnects) }
+                    </span>
+                    <span class=("tag", if stats.decode_failures.messages > 0 { "is-danger" } else { "is-light" })>
+                        { format!(
+                            "Decode failures: {} ({})",
+                            stats.decode_failures.messages,
+                            format_bytes(stats.decode_failures.bytes)
+                        ) }
+                    </span>
+                    {
+                        for stats.time_in_status(self.now).into_iter().map(|(status, millis)| html! {
+                            <span class="tag is-light">
+                                { format!("{}: {}", status.name(), format_duration(millis)) }
+                            </span>
+                        })
+                    }
+                </div>
+                { self.render_table(stats) }
+                <button class="button is-small" onclick=self.link.callback(|_| Msg::Refresh)>
+                    { "Refresh" }
+                </button>
+            </div>
+        }
+    }
+}
+
+impl TrafficStatsView {
+    fn render_table(&self, stats: &TrafficStats) -> Html {
+        let mut types = stats
+            .sent
+            .keys()
+            .chain(stats.received.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|r#type| {
+                let sent = stats.sent.get(r#type).copied().unwrap_or_default();
+                let received = stats.received.get(r#type).copied().unwrap_or_default();
+                (r#type, sent, received)
+            })
+            .collect::<Vec<_>>();
+        // The types taking the most bandwidth first.
+        types.sort_by_key(|(_, sent, received)| std::cmp::Reverse(sent.bytes + received.bytes));
+
+        let row = |name: Html, sent: Counter, received: Counter| {
+            html! {
+                <tr>
+                    <th>{ name }</th>
+                    <td class="has-text-right">{ sent.messages }</td>
+                    <td class="has-text-right">{ format_bytes(sent.bytes) }</td>
+                    <td class="has-text-right">{ received.messages }</td>
+                    <td class="has-text-right">{ format_bytes(received.bytes) }</td>
+                </tr>
+            }
+        };
+
+        html! {
+            <table class="table is-narrow is-fullwidth is-size-7">
+                <thead>
+                    <tr>
+                        <th>{ "Type" }</th>
+                        <th class="has-text-right">{ "Sent" }</th>
+                        <th class="has-text-right">{ "Sent bytes" }</th>
+                        <th class="has-text-right">{ "Received" }</th>
+                        <th class="has-text-right">{ "Received bytes" }</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {
+                        for types.into_iter().map(|(r#type, sent, received)| {
+                            row(html! { <code>{ r#type }</code> }, sent, received)
+                        })
+                    }
+                </tbody>
+                <tfoot>
+                    { row(html! { "Total" }, stats.total_sent(), stats.total_received()) }
+                </tfoot>
+            </table>
+        }
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+fn format_duration(millis: f64) -> String {
+    let seconds = (millis / 1000.0).max(0.0) as u64;
+    if seconds < 60 {
+        format!("{}s", seconds)
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
@@ -1,14 +1,23 @@
 use derive_more::From;
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+use wasm_bindgen::JsValue;
 use yew::prelude::*;
+use yew::services::reader::{FileData, ReaderService, ReaderTask};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
 use yewtil::NeqAssign;
 
 use crate::agents::game_ws_mgr::*;
 use crate::agents::notifications::*;
+use crate::components::debug_history::{self, *};
+use crate::components::debug_scripts::*;
 use crate::html::*;
+use crate::services::files;
 use crate::wire;
 
 const MAX_HISTORY_LEN: usize = 500;
+const JSONL_MIME_TYPE: &str = "application/x-ndjson";
+const HAR_MIME_TYPE: &str = "application/json";
 
 pub struct WebSocketDebugConsole {
     link: ComponentLink<Self>,
@@ -17,12 +26,31 @@ pub struct WebSocketDebugConsole {
     ws_agent: Box<dyn Bridge<GameWsMgr>>,
     ws_status: WebSocketStatus,
 
-    ws_history: VecDeque<(usize, String)>,
+    ws_history: VecDeque<HistoryEntry>,
     ws_history_last_id: usize,
+    history_ref: NodeRef,
+    filter: HistoryFilter,
+    /// The entries showing their pretty-printed JSON.
+    expanded: HashSet<usize>,
+    /// Whether the history follows the new entries.
+    auto_scroll: bool,
+    /// The entries whose messages are to be sent again.
+    selected: HashSet<usize>,
+
+    reader: ReaderService,
+    _reader_task: Option<ReaderTask>,
 
     game_id: String,
     player_id: String,
     ws_message: String,
+
+    scripts: Vec<Script>,
+    /// The saved script being edited, if it's not a new one.
+    edited_script: Option<usize>,
+    script_name: String,
+    script_steps: String,
+    script_run: Option<ScriptRun>,
+    _script_task: Option<TimeoutTask>,
 }
 
 #[derive(From, Debug)]
@@ -38,6 +66,26 @@ pub enum Command {
     SendPing,
     SendMessage,
     CloseWebSocket,
+    ToggleDirection(EntryDirection),
+    FilterType(Option<String>),
+    ToggleEntry(usize),
+    ToggleAutoScroll,
+    ClearHistory,
+    UseTemplate(usize),
+    EditScript(Option<usize>),
+    SaveScript,
+    DeleteScript,
+    AppendMessageToScript,
+    RunScript,
+    PauseScript,
+    ResumeScript,
+    StopScript,
+    ContinueScript,
+    ExportJsonl,
+    ExportHar,
+    ImportHistory(web_sys::File),
+    ToggleSelected(usize),
+    ResendSelected,
 }
 
 #[derive(Debug, From)]
@@ -45,6 +93,10 @@ pub enum Event {
     GameIdChanged(String),
     PlayerIdChanged(String),
     WsMessageChanged(String),
+    SearchChanged(String),
+    HistoryFileLoaded(FileData),
+    ScriptNameChanged(String),
+    ScriptStepsChanged(String),
     #[from]
     WebSocketMessage(GameWsResponse),
 }
@@ -75,10 +127,25 @@ impl Component for WebSocketDebugConsole {
             ws_status: WebSocketStatus::NotConnected,
             ws_history: VecDeque::with_capacity(MAX_HISTORY_LEN),
             ws_history_last_id: 0, //std::usize::MAX,
+            history_ref: NodeRef::default(),
+            filter: HistoryFilter::default(),
+            expanded: HashSet::new(),
+            auto_scroll: true,
+            selected: HashSet::new(),
+
+            reader: ReaderService::new(),
+            _reader_task: None,
 
             game_id: String::from(""),
             player_id: String::from(""),
             ws_message: String::from(""),
+
+            scripts: load_scripts(),
+            edited_script: None,
+            script_name: String::new(),
+            script_steps: String::new(),
+            script_run: None,
+            _script_task: None,
         }
     }
 
@@ -99,6 +166,7 @@ impl Component for WebSocketDebugConsole {
                         .send(GameWsRequest::JoinRound(GameWsConnectionInfo {
                             game_id: self.game_id.clone(),
                             player_id: self.player_id.clone(),
+                            spectator: None,
                         }));
                     false
                 }
@@ -106,97 +174,353 @@ impl Component for WebSocketDebugConsole {
                     self.send_on_ws(wire::Message::Ping);
                     true
                 }
-                Command::SendMessage => {
-                    match serde_json::from_str::<wire::Message>(&self.ws_message) {
-                        Ok(message) => {
-                            self.send_on_ws(message);
-                            true
+                Command::SendMessage => match wire::Message::parse_strict(&self.ws_message) {
+                    Ok(message) => {
+                        self.send_on_ws(message);
+                        true
+                    }
+                    Err(e) => {
+                        self.push_in_history(EntryDirection::Local, EntryContent::Error(e));
+                        true
+                    }
+                },
+                Command::EditScript(index) => {
+                    let script = index
+                        .and_then(|index| self.scripts.get(index))
+                        .cloned()
+                        .unwrap_or_default();
+                    self.edited_script = index;
+                    self.script_name = script.name.clone();
+                    self.script_steps = script.steps_to_string();
+                    true
+                }
+                Command::SaveScript => {
+                    let steps = match Script::parse_steps(&self.script_steps) {
+                        Ok(steps) => steps,
+                        Err(e) => {
+                            self.notify_error(format!("The script is not correct: {}", e));
+                            return false;
+                        }
+                    };
+                    let script = Script {
+                        name: self.script_name.trim().to_string(),
+                        steps,
+                    };
+                    match self
+                        .edited_script
+                        .and_then(|index| self.scripts.get_mut(index))
+                    {
+                        Some(saved) => *saved = script,
+                        None => {
+                            self.scripts.push(script);
+                            self.edited_script = Some(self.scripts.len() - 1);
                         }
+                    }
+                    save_scripts(&self.scripts);
+                    true
+                }
+                Command::DeleteScript => {
+                    if let Some(index) = self.edited_script.take() {
+                        self.scripts.remove(index);
+                        save_scripts(&self.scripts);
+                    }
+                    self.script_name.clear();
+                    self.script_steps.clear();
+                    true
+                }
+                Command::AppendMessageToScript => {
+                    let message = match wire::Message::parse_strict(&self.ws_message) {
+                        Ok(message) => message,
                         Err(e) => {
-                            self.push_in_history(format!(
-                                "ERROR: Message is not correct JSON: {}",
-                                e
-                            ));
-                            true
+                            self.notify_error(e);
+                            return false;
+                        }
+                    };
+                    let mut steps = if self.script_steps.trim().is_empty() {
+                        Vec::new()
+                    } else {
+                        match Script::parse_steps(&self.script_steps) {
+                            Ok(steps) => steps,
+                            Err(e) => {
+                                self.notify_error(format!("The script is not correct: {}", e));
+                                return false;
+                            }
+                        }
+                    };
+                    steps.push(ScriptStep::Send(message));
+                    self.script_steps = serde_json::to_string_pretty(&steps).unwrap_or_default();
+                    true
+                }
+                Command::RunScript => {
+                    let steps = match Script::parse_steps(&self.script_steps) {
+                        Ok(steps) => steps,
+                        Err(e) => {
+                            self.notify_error(format!("The script is not correct: {}", e));
+                            return false;
                         }
+                    };
+                    let script = Script {
+                        name: self.script_name.trim().to_string(),
+                        steps,
+                    };
+                    self.stop_script("stopped");
+                    self.push_event(format!("Running the script {:?}", script.name));
+                    self.script_run = Some(ScriptRun::new(&script));
+                    self.continue_script();
+                    true
+                }
+                Command::PauseScript => {
+                    if let Some(run) = &mut self.script_run {
+                        run.paused = true;
+                    }
+                    // A pending delay is cut short by resuming.
+                    self._script_task = None;
+                    true
+                }
+                Command::ResumeScript => {
+                    if let Some(run) = &mut self.script_run {
+                        run.paused = false;
                     }
+                    self.continue_script();
+                    true
                 }
+                Command::StopScript => {
+                    self.stop_script("stopped");
+                    true
+                }
+                Command::ContinueScript => {
+                    self._script_task = None;
+                    self.continue_script();
+                    true
+                }
+                Command::UseTemplate(index) => match wire::Message::examples().get(index) {
+                    Some(example) => {
+                        let template = serde_json::to_string_pretty(example).unwrap_or_default();
+                        self.ws_message.neq_assign(template)
+                    }
+                    None => false,
+                },
                 Command::CloseWebSocket => {
-                    self.ws_agent.send(GameWsRequest::CloseSocket);
+                    let close = wire::Close::new(wire::CloseReason::Leaving);
+                    self.ws_agent.send(GameWsRequest::Close(close));
                     false
                 }
+                Command::ToggleDirection(direction) => {
+                    self.filter.toggle_direction(direction);
+                    true
+                }
+                Command::FilterType(message_type) => {
+                    self.filter.message_type.neq_assign(message_type)
+                }
+                Command::ToggleEntry(id) => {
+                    if !self.expanded.remove(&id) {
+                        self.expanded.insert(id);
+                    }
+                    true
+                }
+                Command::ToggleAutoScroll => {
+                    self.auto_scroll = !self.auto_scroll;
+                    true
+                }
+                Command::ClearHistory => {
+                    self.ws_history.clear();
+                    self.expanded.clear();
+                    self.selected.clear();
+                    true
+                }
+                Command::ExportJsonl => {
+                    let jsonl = debug_history::to_jsonl(&self.metadata(), &self.ws_history);
+                    let filename = format!("ws-history-{}.jsonl", self.game_id);
+                    if let Err(e) = files::download(&filename, JSONL_MIME_TYPE, &jsonl) {
+                        self.notify_error(format!("Failed to export the history: {}", e));
+                    }
+                    false
+                }
+                Command::ExportHar => {
+                    let started = self
+                        .ws_history
+                        .front()
+                        .map_or_else(js_sys::Date::now, |entry| entry.timestamp);
+                    let started = js_sys::Date::new(&JsValue::from_f64(started)).to_iso_string();
+                    let har = debug_history::to_har(
+                        &self.metadata(),
+                        &self.ws_history,
+                        &String::from(started),
+                    );
+                    let filename = format!("ws-history-{}.har", self.game_id);
+                    if let Err(e) = files::download(&filename, HAR_MIME_TYPE, &har) {
+                        self.notify_error(format!("Failed to export the history: {}", e));
+                    }
+                    false
+                }
+                Command::ImportHistory(file) => {
+                    let callback = self.link.callback(Event::HistoryFileLoaded);
+                    match self.reader.read_file(file, callback) {
+                        Ok(task) => self._reader_task = Some(task),
+                        Err(e) => self.notify_error(format!("Failed to read the file: {}", e)),
+                    }
+                    false
+                }
+                Command::ToggleSelected(id) => {
+                    if !self.selected.remove(&id) {
+                        self.selected.insert(id);
+                    }
+                    true
+                }
+                Command::ResendSelected => {
+                    let messages = self
+                        .ws_history
+                        .iter()
+                        .filter(|entry| self.selected.contains(&entry.id))
+                        .filter_map(|entry| match &entry.content {
+                            EntryContent::Message(message) => Some(message.clone()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    self.selected.clear();
+                    for message in messages {
+                        self.send_on_ws(message);
+                    }
+                    true
+                }
             },
             Msg::Event(event) => match event {
                 Event::GameIdChanged(game_id) => self.game_id.neq_assign(game_id),
                 Event::PlayerIdChanged(player_id) => self.player_id.neq_assign(player_id),
                 Event::WsMessageChanged(ws_message) => self.ws_message.neq_assign(ws_message),
+                Event::SearchChanged(search) => self.filter.search.neq_assign(search),
+                Event::ScriptNameChanged(name) => self.script_name.neq_assign(name),
+                Event::ScriptStepsChanged(steps) => self.script_steps.neq_assign(steps),
+                Event::HistoryFileLoaded(file) => {
+                    self._reader_task = None;
+                    match debug_history::import(&String::from_utf8_lossy(&file.content)) {
+                        Ok((metadata, entries)) => {
+                            self.ws_history.clear();
+                            self.expanded.clear();
+                            self.selected.clear();
+                            let count = entries.len();
+                            for entry in entries {
+                                self.push_entry(entry);
+                            }
+                            self.push_event(format!(
+                                "Imported {} entries from {} (game {:?}, player {:?}, build {})",
+                                count,
+                                file.name,
+                                metadata.game_id,
+                                metadata.player_id,
+                                metadata.commit
+                            ));
+                        }
+                        Err(e) => {
+                            self.notify_error(format!("Failed to import {}: {}", file.name, e));
+                        }
+                    }
+                    true
+                }
                 Event::WebSocketMessage(ws_msg) => match ws_msg {
                     GameWsResponse::Connecting(info) => {
                         self.change_status(WebSocketStatus::Pending(info));
-                        self.push_in_history("Connecting...");
+                        self.push_event("Connecting...");
                         true
                     }
                     GameWsResponse::Connected(info) => {
                         self.change_status(WebSocketStatus::Connected(info));
-                        self.push_in_history("Connected");
+                        self.push_event("Connected");
                         true
                     }
-                    GameWsResponse::Closed => {
+                    GameWsResponse::Closed(cause) => {
                         self.change_status(WebSocketStatus::NotConnected);
-                        self.push_in_history("Disconnected");
+                        match cause {
+                            CloseCause::Client(_) => self.push_event("Closed by the client"),
+                            CloseCause::Server(close) => self.push_event(format!(
+                                "Closed by the server: {}",
+                                close.user_message()
+                            )),
+                            CloseCause::Lost => self.push_event("Disconnected"),
+                        }
+                        self.stop_script("was interrupted by the disconnection");
                         true
                     }
                     GameWsResponse::FailedToConnect(reason) => {
                         self.change_status(WebSocketStatus::NotConnected);
-                        self.push_in_history(format!("Failed to connect: {}", reason));
+                        self.push_event(format!("Failed to connect: {}", reason));
                         true
                     }
                     GameWsResponse::ErrorOccurred => {
-                        self.push_in_history("An unknown error occurred.");
+                        self.push_event("An unknown error occurred.");
                         self.change_status(WebSocketStatus::NotConnected);
+                        self.stop_script("was interrupted by an error");
                         true
                     }
                     GameWsResponse::Sent(data) => {
-                        // Try to format (serialize) the data as JSON
-                        let as_json =
-                            serde_json::to_string(&data).unwrap_or_else(|_| format!("{:?}", &data));
-
-                        self.push_in_history(format!("> {}", as_json));
+                        self.push_in_history(EntryDirection::Sent, EntryContent::Message(data.0));
                         true
                     }
                     GameWsResponse::Received(data) => {
-                        // Try to format (serialize) the data as JSON
-                        let as_json =
-                            serde_json::to_string(&data).unwrap_or_else(|_| format!("{:?}", &data));
-
-                        self.push_in_history(format!("< {}", as_json));
+                        let awaited = self
+                            .script_run
+                            .as_mut()
+                            .map_or(false, |run| run.received(&data.0));
+                        // Unknown messages are kept as they were received
+                        self.push_in_history(
+                            EntryDirection::Received,
+                            EntryContent::Message(data.0),
+                        );
+                        if awaited {
+                            self.continue_script();
+                        }
                         true
                     }
                     GameWsResponse::ReceivedError(error) => {
-                        self.push_in_history(format!(
-                            "Failed to decode received data: {:?}",
-                            error
-                        ));
+                        self.push_in_history(
+                            EntryDirection::Received,
+                            EntryContent::Error(format!(
+                                "Failed to decode received data: {}",
+                                error
+                            )),
+                        );
                         true
                     }
                     GameWsResponse::WebSocketStatus(status) => self.change_status(status),
+                    GameWsResponse::Stats(_) => false,
                 },
             },
         }
     }
 
+    fn rendered(&mut self, _first_render: bool) {
+        if !self.auto_scroll {
+            return;
+        }
+        if let Some(history) = self.history_ref.cast::<web_sys::Element>() {
+            history.set_scroll_top(history.scroll_height());
+        }
+    }
+
     fn view(&self) -> Html {
         let connected = self.ws_status.is_connected();
         let pending = self.ws_status.is_pending();
         let loading_class = if pending { Some("is-loading") } else { None };
 
-        let textarea_color = if self.ws_message.is_empty() {
-            Some("is-info".to_string())
-        } else if serde_json::from_str::<serde_json::Value>(&self.ws_message).is_ok() {
-            Some("is-success".to_string())
+        let (textarea_color, validation) = if self.ws_message.is_empty() {
+            ("is-info", None)
         } else {
-            Some("is-danger".to_string())
+            match wire::Message::parse_strict(&self.ws_message) {
+                Ok(message) => (
+                    "is-success",
+                    Some(format!("Valid {} message", message.type_tag())),
+                ),
+                Err(e) => ("is-danger", Some(e)),
+            }
         };
+        let on_template_change = self.link.callback(|event: ChangeData| match event {
+            // The first option only tells what the list is for.
+            ChangeData::Select(select) => match select.selected_index() {
+                index if index > 0 => Command::UseTemplate(index as usize - 1),
+                _ => Command::Update,
+            },
+            _ => Command::Update,
+        });
 
         let on_game_id_change = self
             .link
@@ -212,13 +536,16 @@ impl Component for WebSocketDebugConsole {
             <div class="columns">
                 <div class="column is-two-thirds is-flex is-flex-column">
                     <h2 class="title is-size-4">{ "Message history - " }{ self.ws_history.len() }</h2>
-                    <pre class="ws-console">
+                    { self.render_history_toolbar() }
+                    { self.render_history_files() }
+                    <div class="ws-console" ref=self.history_ref.clone()>
                         {
-                            for self.ws_history.iter().map(|(id, line)| {
-                                html! { <p>{ format!("{}. {}", id, line) }</p> }
-                            })
+                            for self.ws_history
+                                .iter()
+                                .filter(|entry| self.filter.matches(entry))
+                                .map(|entry| self.render_entry(entry))
                         }
-                    </pre>
+                    </div>
                 </div>
                 <div class="column">
                     <TextInputField
@@ -252,10 +579,25 @@ impl Component for WebSocketDebugConsole {
                             { "Send Ping on WebSocket" }
                         </button>
                     </div>
+                    <div class="field">
+                        <div class="control">
+                            <div class="select is-fullwidth">
+                                <select onchange=on_template_change aria-label="Message template">
+                                    <option selected=true>{ "Start from a template…" }</option>
+                                    {
+                                        for wire::Message::KNOWN_TYPES.iter().map(|r#type| html! {
+                                            <option selected=false>{ r#type }</option>
+                                        })
+                                    }
+                                </select>
+                            </div>
+                        </div>
+                    </div>
                     <TextAreaField
                         label="Message to send"
                         placeholder="Enter a message to send on the WebSocket."
-                        class=textarea_color
+                        class=Some(textarea_color.to_string())
+                        help=validation
                         value=&self.ws_message
                         oninput=on_msg_change
                         />
@@ -264,6 +606,7 @@ impl Component for WebSocketDebugConsole {
                             onclick=self.link.callback(move |_| Command::SendMessage)>
                         { "Send" }
                     </button>
+                    { self.render_scripts() }
                 </div>
             </div>
         }
@@ -286,18 +629,440 @@ impl WebSocketDebugConsole {
         self.ws_status.neq_assign(status)
     }
 
-    fn push_in_history(&mut self, line: impl ToString) {
+    fn push_event(&mut self, event: impl ToString) {
+        self.push_in_history(
+            EntryDirection::Local,
+            EntryContent::Event(event.to_string()),
+        );
+    }
+
+    fn push_in_history(&mut self, direction: EntryDirection, content: EntryContent) {
+        self.push_entry(HistoryEntry::new(
+            0,
+            js_sys::Date::now(),
+            direction,
+            content,
+        ));
+    }
+
+    /// Adds the entry to the history, under a new ID.
+    fn push_entry(&mut self, mut entry: HistoryEntry) {
         while self.ws_history.len() >= MAX_HISTORY_LEN {
-            self.ws_history.pop_back();
+            if let Some(entry) = self.ws_history.pop_front() {
+                self.expanded.remove(&entry.id);
+                self.selected.remove(&entry.id);
+            }
         }
 
         self.ws_history_last_id = self.ws_history_last_id.wrapping_add(1);
+        entry.id = self.ws_history_last_id;
+
+        self.ws_history.push_back(entry);
+    }
+
+    /// Runs the steps of the script until one of them waits.
+    fn continue_script(&mut self) {
+        loop {
+            let run = match &mut self.script_run {
+                Some(run) if !run.paused && run.waiting_for.is_none() => run,
+                _ => return,
+            };
+            match run.advance() {
+                RunAction::Send(message) => self.send_on_ws(message),
+                RunAction::Wait(millis) => {
+                    self._script_task = Some(TimeoutService::new().spawn(
+                        Duration::from_millis(millis.into()),
+                        self.link.callback(|_| Command::ContinueScript),
+                    ));
+                    return;
+                }
+                RunAction::WaitFor(_) => return,
+                RunAction::Done => {
+                    self.stop_script("finished");
+                    return;
+                }
+            }
+        }
+    }
 
-        self.ws_history
-            .push_front((self.ws_history_last_id, line.to_string()));
+    fn stop_script(&mut self, why: &str) {
+        self._script_task = None;
+        if let Some(run) = self.script_run.take() {
+            self.push_event(format!("The script {:?} {}", run.name, why));
+        }
+    }
+
+    fn metadata(&self) -> HistoryMetadata {
+        HistoryMetadata {
+            game_id: self.game_id.clone(),
+            player_id: self.player_id.clone(),
+            commit: env!("GIT_COMMIT_HASH").to_string(),
+        }
+    }
+
+    fn render_history_toolbar(&self) -> Html {
+        let render_direction = |direction, name| {
+            let is_shown = self.filter.shows_direction(direction);
+            html! {
+                <button
+                    class=("button is-small", if is_shown { "is-info is-selected" } else { "" })
+                    aria-pressed=if is_shown { "true" } else { "false" }
+                    onclick=self.link.callback(move |_| Command::ToggleDirection(direction))
+                    >
+                    { name }
+                </button>
+            }
+        };
+
+        let types = message_types(&self.ws_history);
+        let on_type_change = {
+            let types = types.clone();
+            self.link.callback(move |event: ChangeData| match event {
+                ChangeData::Select(select) => {
+                    // The first option shows all the types.
+                    let index = select.selected_index().max(0) as usize;
+                    Command::FilterType(
+                        index
+                            .checked_sub(1)
+                            .and_then(|i| types.iter().nth(i).cloned()),
+                    )
+                }
+                _ => Command::FilterType(None),
+            })
+        };
+        let on_search = self
+            .link
+            .callback(|e: InputData| Event::SearchChanged(e.value));
+
+        html! {
+            <div class="field is-grouped is-grouped-multiline ws-console-toolbar">
+                <div class="control">
+                    <div class="buttons has-addons">
+                        { render_direction(EntryDirection::Sent, "Sent") }
+                        { render_direction(EntryDirection::Received, "Received") }
+                        { render_direction(EntryDirection::Local, "Events") }
+                    </div>
+                </div>
+                <div class="control">
+                    <div class="select is-small">
+                        <select onchange=on_type_change aria-label="Message type">
+                            <option selected=self.filter.message_type.is_none()>{ "All types" }</option>
+                            {
+                                for types.iter().map(|message_type| html! {
+                                    <option selected=self.filter.message_type.as_ref() == Some(message_type)>
+                                        { message_type }
+                                    </option>
+                                })
+                            }
+                        </select>
+                    </div>
+                </div>
+                <div class="control is-expanded">
+                    <input
+                        class="input is-small"
+                        type="search"
+                        placeholder="Search"
+                        aria-label="Search the history"
+                        value=&self.filter.search
+                        oninput=on_search
+                        />
+                </div>
+                <div class="control">
+                    <button
+                        class="button is-small"
+                        onclick=self.link.callback(|_| Command::ToggleAutoScroll)
+                        >
+                        { if self.auto_scroll { "Pause auto-scroll" } else { "Resume auto-scroll" } }
+                    </button>
+                </div>
+                <div class="control">
+                    <button
+                        class="button is-small is-danger is-outlined"
+                        onclick=self.link.callback(|_| Command::ClearHistory)
+                        >
+                        { "Clear" }
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_history_files(&self) -> Html {
+        let on_import = self.link.callback(|event: ChangeData| match event {
+            ChangeData::Files(files) => match files.get(0) {
+                Some(file) => Msg::from(Command::ImportHistory(file)),
+                None => Msg::from(Command::Update),
+            },
+            _ => Msg::from(Command::Update),
+        });
+        let has_history = !self.ws_history.is_empty();
+
+        html! {
+            <div class="field is-grouped is-grouped-multiline ws-console-toolbar">
+                <div class="control">
+                    <div class="buttons has-addons">
+                        <button
+                            class="button is-small"
+                            disabled=!has_history
+                            onclick=self.link.callback(|_| Command::ExportJsonl)
+                            >
+                            { "Export JSONL" }
+                        </button>
+                        <button
+                            class="button is-small"
+                            disabled=!has_history
+                            onclick=self.link.callback(|_| Command::ExportHar)
+                            >
+                            { "Export HAR" }
+                        </button>
+                    </div>
+                </div>
+                <div class="control">
+                    <div class="file is-small">
+                        <label class="file-label">
+                            <input
+                                class="file-input"
+                                type="file"
+                                accept=".jsonl,.har,.json"
+                                onchange=on_import
+                                />
+                            <span class="file-cta">
+                                <span class="file-label">{ "Import…" }</span>
+                            </span>
+                        </label>
+                    </div>
+                </div>
+                <div class="control">
+                    <button
+                        class="button is-small is-primary"
+                        disabled=(self.selected.is_empty() || !self.ws_status.is_connected())
+                        onclick=self.link.callback(|_| Command::ResendSelected)
+                        >
+                        { format!("Re-send selected ({})", self.selected.len()) }
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_scripts(&self) -> Html {
+        let on_script_change = self.link.callback(|event: ChangeData| match event {
+            // The first option starts a new script.
+            ChangeData::Select(select) => {
+                Command::EditScript((select.selected_index().max(0) as usize).checked_sub(1))
+            }
+            _ => Command::Update,
+        });
+        let on_name_change = self
+            .link
+            .callback(|e: InputData| Event::ScriptNameChanged(e.value));
+        let on_steps_change = self
+            .link
+            .callback(|e: InputData| Event::ScriptStepsChanged(e.value));
+
+        let (steps_color, validation) = if self.script_steps.trim().is_empty() {
+            ("is-info", None)
+        } else {
+            match Script::parse_steps(&self.script_steps) {
+                Ok(steps) => ("is-success", Some(format!("{} steps", steps.len()))),
+                Err(e) => ("is-danger", Some(e)),
+            }
+        };
+        let is_valid = steps_color == "is-success";
+        let connected = self.ws_status.is_connected();
+
+        html! {
+            <div class="ws-console-scripts">
+                <h3 class="title is-size-5">{ "Scripts" }</h3>
+                <div class="field">
+                    <div class="control">
+                        <div class="select is-fullwidth">
+                            <select onchange=on_script_change aria-label="Script">
+                                <option selected=self.edited_script.is_none()>{ "New script" }</option>
+                                {
+                                    for self.scripts.iter().enumerate().map(|(index, script)| html! {
+                                        <option selected=self.edited_script == Some(index)>
+                                            { &script.name }
+                                        </option>
+                                    })
+                                }
+                            </select>
+                        </div>
+                    </div>
+                </div>
+                <TextInputField
+                    label="Name"
+                    placeholder="Name the script."
+                    value=&self.script_name
+                    oninput=on_name_change
+                    />
+                <TextAreaField
+                    label="Steps"
+                    placeholder=r#"[{"send": {"type": "START_GAME"}}, {"wait_for": "GAME_STARTED"}, {"delay": 500}]"#
+                    class=Some(steps_color.to_string())
+                    help=validation
+                    value=&self.script_steps
+                    oninput=on_steps_change
+                    />
+                <div class="buttons">
+                    <button
+                        class="button is-small"
+                        onclick=self.link.callback(|_| Command::AppendMessageToScript)
+                        >
+                        { "Add the message to send" }
+                    </button>
+                    <button
+                        class="button is-small is-primary"
+                        disabled=(!is_valid || self.script_name.trim().is_empty())
+                        onclick=self.link.callback(|_| Command::SaveScript)
+                        >
+                        { "Save" }
+                    </button>
+                    <button
+                        class="button is-small is-danger is-outlined"
+                        disabled=self.edited_script.is_none()
+                        onclick=self.link.callback(|_| Command::DeleteScript)
+                        >
+                        { "Delete" }
+                    </button>
+                </div>
+                { self.render_script_run(is_valid && connected) }
+            </div>
+        }
+    }
+
+    fn render_script_run(&self, can_run: bool) -> Html {
+        let run = match &self.script_run {
+            Some(run) => run,
+            None => {
+                return html! {
+                    <button
+                        class="button is-fullwidth is-info"
+                        disabled=!can_run
+                        onclick=self.link.callback(|_| Command::RunScript)
+                        >
+                        { "Run the script" }
+                    </button>
+                }
+            }
+        };
+
+        let status = match &run.waiting_for {
+            Some(r#type) => format!("waiting for {}", r#type),
+            None if run.paused => "paused".to_string(),
+            None => "running".to_string(),
+        };
+        let pause_resume = if run.paused {
+            html! {
+                <button class="button is-info" onclick=self.link.callback(|_| Command::ResumeScript)>
+                    { "Resume" }
+                </button>
+            }
+        } else {
+            html! {
+                <button class="button" onclick=self.link.callback(|_| Command::PauseScript)>
+                    { "Pause" }
+                </button>
+            }
+        };
+
+        html! {
+            <>
+                <p class="help" role="status">
+                    { format!("{}: step {} / {}, {}", run.name, run.position, run.step_count(), status) }
+                </p>
+                <div class="buttons has-addons">
+                    { pause_resume }
+                    <button class="button is-danger is-outlined" onclick=self.link.callback(|_| Command::StopScript)>
+                        { "Stop" }
+                    </button>
+                </div>
+            </>
+        }
+    }
+
+    fn render_entry(&self, entry: &HistoryEntry) -> Html {
+        let (arrow, direction_class) = match entry.direction {
+            EntryDirection::Sent => (">", "is-sent"),
+            EntryDirection::Received => ("<", "is-received"),
+            EntryDirection::Local => ("-", "is-local"),
+        };
+        let is_error = matches!(entry.content, EntryContent::Error(_));
+        let unknown_type = match &entry.content {
+            EntryContent::Message(message) => message.unknown_type(),
+            _ => None,
+        };
+        let is_message = matches!(entry.content, EntryContent::Message(_));
+        let is_expanded = is_message && self.expanded.contains(&entry.id);
+
+        let select = if unknown_type.is_none() && is_message {
+            let id = entry.id;
+            html! {
+                <input
+                    type="checkbox"
+                    checked=self.selected.contains(&id)
+                    aria-label="Select to re-send"
+                    onclick=self.link.callback(move |_| Command::ToggleSelected(id))
+                    />
+            }
+        } else {
+            html! {}
+        };
+
+        let toggle = if is_message {
+            let id = entry.id;
+            html! {
+                <button
+                    class="button is-small is-white ws-console-toggle"
+                    aria-expanded=if is_expanded { "true" } else { "false" }
+                    aria-label="Pretty-print"
+                    onclick=self.link.callback(move |_| Command::ToggleEntry(id))
+                    >
+                    { if is_expanded { "▾" } else { "▸" } }
+                </button>
+            }
+        } else {
+            html! {}
+        };
+        let pretty = if is_expanded { entry.pretty() } else { None };
+        let body = match pretty {
+            Some(pretty) => html! { <pre>{ pretty }</pre> },
+            None => html! { <code>{ entry.summary() }</code> },
+        };
+
+        html! {
+            <div class=("ws-console-entry", direction_class, if is_error { "is-error" } else { "" })>
+                { select }
+                <span class="ws-console-meta">
+                    { format!("{}. {} {}", entry.id, format_time(entry.timestamp), arrow) }
+                </span>
+                {
+                    match unknown_type {
+                        Some(r#type) => html! {
+                            <span class="tag is-warning is-light">{ format!("unknown type {:?}", r#type) }</span>
+                        },
+                        None => html! {},
+                    }
+                }
+                { toggle }
+                { body }
+            </div>
+        }
     }
 
     fn send_on_ws<M: Into<wire::Message>>(&mut self, message: M) {
         self.ws_agent.send(GameWsRequest::Send(WsRequest(message.into())));
     }
 }
+
+/// Formats a timestamp in milliseconds as the local time, to the millisecond.
+fn format_time(timestamp: f64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(timestamp));
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        date.get_hours(),
+        date.get_minutes(),
+        date.get_seconds(),
+        date.get_milliseconds()
+    )
+}
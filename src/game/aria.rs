@@ -0,0 +1,335 @@
+//! Accessible names of the game components, and the announcements read out by
+//! screen readers as the game goes on.
+
+use super::faces::Face;
+use super::GameState;
+use crate::wire;
+
+/// A sentence for the screen readers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Announcement {
+    pub message: String,
+    /// Whether the message interrupts the screen reader, e.g. for errors.
+    pub assertive: bool,
+}
+
+impl Announcement {
+    pub fn polite(message: impl Into<String>) -> Self {
+        Announcement {
+            message: message.into(),
+            assertive: false,
+        }
+    }
+
+    pub fn assertive(message: impl Into<String>) -> Self {
+        Announcement {
+            message: message.into(),
+            assertive: true,
+        }
+    }
+}
+
+/// Short name of a card, e.g. "queen of hearts".
+pub fn card_name(card: &wire::Card, face_up: bool) -> String {
+    if !face_up {
+        return "face down card".to_string();
+    }
+    if let Some(face) = Face::from_state(&card.state) {
+        return face.label();
+    }
+    if !card.name.is_empty() {
+        return card.name.clone();
+    }
+    let state = &card.state;
+    match (&state.value, &state.suit) {
+        (None, None) => "card".to_string(),
+        (value, suit) => format!(
+            "{} {}",
+            value.as_deref().unwrap_or_default(),
+            suit.as_deref().unwrap_or_default()
+        )
+        .trim()
+        .to_string(),
+    }
+}
+
+/// Full description of a card: its name, properties and description.
+pub fn card_label(card: &wire::Card, face_up: bool) -> String {
+    let mut parts = vec![card_name(card, face_up)];
+    if face_up {
+        parts.extend(
+            card.state
+                .properties
+                .iter()
+                .map(|(name, property)| format!("{} {}", name, property_text(property))),
+        );
+        if !card.description.is_empty() {
+            parts.push(card.description.clone());
+        }
+    }
+    parts.join(", ")
+}
+
+fn property_text(property: &wire::CardProperty) -> String {
+    match property {
+        wire::CardProperty::Bool(true) => "yes".to_string(),
+        wire::CardProperty::Bool(false) => "no".to_string(),
+        wire::CardProperty::Integer(number) => number.to_string(),
+        wire::CardProperty::Float(number) => number.to_string(),
+        wire::CardProperty::Color { color } => color.clone(),
+        wire::CardProperty::Text(text) => text.clone(),
+        wire::CardProperty::List(items) => items
+            .iter()
+            .map(property_text)
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+/// Short name of a component, to mention it in a sentence.
+pub fn component_name(state: &GameState, id: &wire::ComponentId, player_id: &str) -> String {
+    match state.components.get(id) {
+        Some(wire::Component::Card(card)) => card_name(card, state.is_face_visible(id, player_id)),
+        Some(wire::Component::Hand(hand)) => match &hand.owner {
+            Some(owner) if owner == player_id => "your hand".to_string(),
+            Some(owner) => format!("the hand of {}", state.player_name(owner)),
+            None => format!("hand {}", id.as_str()),
+        },
+        _ => id.as_str().to_string(),
+    }
+}
+
+/// Accessible name of a rendered component.
+pub fn component_label(state: &GameState, id: &wire::ComponentId, player_id: &str) -> String {
+    let label = match state.components.get(id) {
+        Some(wire::Component::Card(card)) => card_label(card, state.is_face_visible(id, player_id)),
+        Some(wire::Component::Hand(hand)) => {
+            let count = match hand.cards.len() {
+                1 => "1 card".to_string(),
+                count => format!("{} cards", count),
+            };
+            format!("{}, {}", component_name(state, id, player_id), count)
+        }
+        _ => format!("unknown component {}", id.as_str()),
+    };
+    capitalize(&label)
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Describes what a message received from the server changed for the player,
+/// given the state of the game before and after applying it.
+pub fn announcements(
+    message: &wire::Message,
+    before: &GameState,
+    after: &GameState,
+    player_id: &str,
+) -> Vec<Announcement> {
+    match message {
+        wire::Message::PlayerConnected(player) if player.id() != player_id => {
+            vec![Announcement::polite(format!(
+                "{} joined the game",
+                player.username
+            ))]
+        }
+        wire::Message::PlayerDisconnected(player) => vec![Announcement::polite(format!(
+            "{} lost their connection",
+            player.username
+        ))],
+        wire::Message::PlayerReconnected(player) if player.id() != player_id => {
+            vec![Announcement::polite(format!("{} is back", player.username))]
+        }
+        wire::Message::PlayerLeft(player) if player.id() != player_id => {
+            let message = if player.kicked {
+                format!("{} was removed from the game", player.username)
+            } else {
+                format!("{} left the game", player.username)
+            };
+            vec![Announcement::polite(message)]
+        }
+        // The turn of the player is announced by the awaited actions.
+        wire::Message::TurnChanged(turn) if turn.player_id != player_id => {
+            vec![Announcement::polite(format!(
+                "It is the turn of {}",
+                after.player_name(&turn.player_id)
+            ))]
+        }
+        wire::Message::GameStarted => vec![Announcement::polite("The game started")],
+        wire::Message::GameFinished(finished) => {
+            let message = if finished.winners.iter().any(|winner| winner == player_id) {
+                "You won the game".to_string()
+            } else if finished.winners.is_empty() {
+                "The game is over".to_string()
+            } else {
+                let winners = finished
+                    .winners
+                    .iter()
+                    .map(|winner| after.player_name(winner))
+                    .collect::<Vec<_>>();
+                format!("{} won the game", winners.join(" and "))
+            };
+            vec![Announcement::polite(message)]
+        }
+        wire::Message::Error(error) => vec![Announcement::assertive(format!(
+            "Error: {}",
+            error.messages.join(". ")
+        ))],
+        wire::Message::ActionAwaited(_)
+            if before.awaited_actions.is_empty() && !after.awaited_actions.is_empty() =>
+        {
+            vec![Announcement::polite("Your turn")]
+        }
+        wire::Message::ComponentsUpdates(_) => card_moves(before, after, player_id),
+        _ => Vec::new(),
+    }
+}
+
+/// Describes the cards which were taken from or put into the hands of the
+/// players.
+fn card_moves(before: &GameState, after: &GameState, player_id: &str) -> Vec<Announcement> {
+    let who = |owner: &str| {
+        if owner == player_id {
+            "You".to_string()
+        } else {
+            after.player_name(owner).to_string()
+        }
+    };
+
+    after
+        .components
+        .iter()
+        .filter_map(|(id, component)| match component {
+            // Cards created by the update, e.g. when joining, were not moved.
+            wire::Component::Card(card) if before.components.contains_key(id) => Some((id, card)),
+            _ => None,
+        })
+        .filter_map(|(id, card)| {
+            let from = before.hand_of(id);
+            let to = after.hand_of(id);
+            if from.map(|(hand, _)| hand) == to.map(|(hand, _)| hand) {
+                return None;
+            }
+
+            let name = match Face::from_state(&card.state) {
+                _ if !after.is_face_visible(id, player_id) => "a card".to_string(),
+                Some(face) => format!("the {}", face.label()),
+                None => card_name(card, true),
+            };
+            let from_owner = from.and_then(|(_, hand)| hand.owner.as_deref());
+            let to_owner = to.and_then(|(_, hand)| hand.owner.as_deref());
+            match (from_owner, to_owner) {
+                (Some(owner), _) => Some(format!("{} played {}", who(owner), name)),
+                (None, Some(owner)) => Some(format!("{} drew {}", who(owner), name)),
+                (None, None) => None,
+            }
+        })
+        .map(Announcement::polite)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(json: &str) -> wire::Message {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn apply(state: &GameState, json: &str) -> (wire::Message, GameState) {
+        let message = decode(json);
+        let mut after = state.clone();
+        after.apply(&message);
+        (message, after)
+    }
+
+    #[test]
+    fn labels_cards() {
+        let card: wire::Card = serde_json::from_str(
+            r#"{
+                "name": "HQ", "description": "Worth ten points",
+                "front_image": null, "back_image": null,
+                "state": { "suit": "hearts", "value": "Q", "properties": { "cost": 3 } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(card_name(&card, true), "queen of hearts");
+        assert_eq!(
+            card_label(&card, true),
+            "queen of hearts, cost 3, Worth ten points"
+        );
+        assert_eq!(card_label(&card, false), "face down card");
+    }
+
+    #[test]
+    fn announces_played_cards() {
+        let (_, state) = apply(
+            &GameState::default(),
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    { "type": "Create", "id": "c1", "component": {
+                        "type": "Card", "name": "HQ", "front_image": null, "back_image": null,
+                        "state": { "suit": "hearts", "value": "Q" }
+                    } },
+                    { "type": "Create", "id": "alice", "component": {
+                        "type": "Hand", "cards": ["c1"], "owner": "alice"
+                    } }
+                ]
+            }"#,
+        );
+        let (message, after) = apply(
+            &state,
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    { "type": "Create", "id": "alice", "component": {
+                        "type": "Hand", "cards": [], "owner": "alice"
+                    } },
+                    { "type": "Create", "id": "bob", "component": {
+                        "type": "Hand", "cards": ["c1"], "owner": "bob"
+                    } }
+                ]
+            }"#,
+        );
+
+        assert_eq!(
+            announcements(&message, &state, &after, "bob"),
+            vec![Announcement::polite("alice played the queen of hearts")]
+        );
+    }
+
+    #[test]
+    fn announces_turns_and_errors() {
+        let (message, after) = apply(
+            &GameState::default(),
+            r#"{"type":"ACTION_AWAITED","all_of":[{"type":"OnClick","target_component":"deck"}]}"#,
+        );
+        assert_eq!(
+            announcements(&message, &GameState::default(), &after, "p1"),
+            vec![Announcement::polite("Your turn")]
+        );
+        assert_eq!(announcements(&message, &after, &after, "p1"), vec![]);
+
+        let (_, state) = apply(
+            &after,
+            r#"{"type":"PLAYER_CONNECTED","message":"","player_id":"p2","username":"Bob"}"#,
+        );
+        let (message, after) = apply(&state, r#"{"type":"TURN_CHANGED","player_id":"p2"}"#);
+        assert_eq!(
+            announcements(&message, &state, &after, "p1"),
+            vec![Announcement::polite("It is the turn of Bob")]
+        );
+
+        let (message, after) = apply(&after, r#"{"type":"ERROR","messages":["Not your turn"]}"#);
+        assert_eq!(
+            announcements(&message, &after, &after, "p1"),
+            vec![Announcement::assertive("Error: Not your turn")]
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! What is on the faces of the cards, recognized from the `CardState` the
+//! servers send.
+
+use crate::wire;
+
+/// Name of the property giving the color of a numeric card.
+const COLOR_PROPERTY: &str = "color";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Suit {
+    Spades,
+    Hearts,
+    Diamonds,
+    Clubs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rank {
+    Ace,
+    Number(u8),
+    Jack,
+    Queen,
+    King,
+}
+
+/// What is drawn on the face of a card.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Face {
+    /// A card of the standard 52-card deck.
+    Standard {
+        rank: Rank,
+        suit: Suit,
+    },
+    Joker {
+        red: bool,
+    },
+    /// A value, e.g. a number, printed on a colored card.
+    Colored {
+        value: String,
+        color: Option<String>,
+    },
+}
+
+impl Suit {
+    /// Parses the suits sent by the servers: initials, names and symbols.
+    pub fn parse(suit: &str) -> Option<Suit> {
+        match suit.trim().to_lowercase().as_str() {
+            "s" | "spade" | "spades" | "♠" => Some(Suit::Spades),
+            "h" | "heart" | "hearts" | "♥" => Some(Suit::Hearts),
+            "d" | "diamond" | "diamonds" | "♦" => Some(Suit::Diamonds),
+            "c" | "club" | "clubs" | "♣" => Some(Suit::Clubs),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Suit::Spades => "spades",
+            Suit::Hearts => "hearts",
+            Suit::Diamonds => "diamonds",
+            Suit::Clubs => "clubs",
+        }
+    }
+
+    pub fn is_red(self) -> bool {
+        self == Suit::Hearts || self == Suit::Diamonds
+    }
+}
+
+impl Rank {
+    /// Parses the values sent by the servers, where aces may be `1` and tens
+    /// may be `T`.
+    pub fn parse(value: &str) -> Option<Rank> {
+        match value.trim().to_lowercase().as_str() {
+            "a" | "1" | "ace" => Some(Rank::Ace),
+            "j" | "jack" => Some(Rank::Jack),
+            "q" | "queen" => Some(Rank::Queen),
+            "k" | "king" => Some(Rank::King),
+            "t" => Some(Rank::Number(10)),
+            number => match number.parse() {
+                Ok(number @ 2..=10) => Some(Rank::Number(number)),
+                _ => None,
+            },
+        }
+    }
+
+    /// The label printed in the corners of the card.
+    pub fn label(self) -> String {
+        match self {
+            Rank::Ace => "A".to_string(),
+            Rank::Number(number) => number.to_string(),
+            Rank::Jack => "J".to_string(),
+            Rank::Queen => "Q".to_string(),
+            Rank::King => "K".to_string(),
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Rank::Ace => "ace".to_string(),
+            Rank::Number(number) => number.to_string(),
+            Rank::Jack => "jack".to_string(),
+            Rank::Queen => "queen".to_string(),
+            Rank::King => "king".to_string(),
+        }
+    }
+}
+
+impl Face {
+    /// Finds out what the face of a card looks like. Returns `None` when there
+    /// is nothing to draw, i.e. the card has no value.
+    pub fn from_state(state: &wire::CardState) -> Option<Face> {
+        let suit = state.suit.as_deref().map(str::trim);
+        let value = state.value.as_deref().map(str::trim);
+
+        let is_joker = |s: Option<&str>| s.map_or(false, |s| s.eq_ignore_ascii_case("joker"));
+        if is_joker(value) || is_joker(suit) {
+            let red = match suit {
+                Some(suit) => {
+                    suit.eq_ignore_ascii_case("red")
+                        || Suit::parse(suit).map_or(false, Suit::is_red)
+                }
+                None => false,
+            };
+            return Some(Face::Joker { red });
+        }
+
+        let value = value.filter(|value| !value.is_empty())?;
+        if let (Some(suit), Some(rank)) = (suit.and_then(Suit::parse), Rank::parse(value)) {
+            return Some(Face::Standard { rank, suit });
+        }
+
+        let color = match state.properties.get(COLOR_PROPERTY) {
+            Some(wire::CardProperty::Color { color }) => Some(color.clone()),
+            _ => suit.filter(|suit| !suit.is_empty()).map(str::to_string),
+        };
+        Some(Face::Colored {
+            value: value.to_string(),
+            color,
+        })
+    }
+
+    /// Describes the face in words, e.g. for the `alt` text of its image.
+    pub fn label(&self) -> String {
+        match self {
+            Face::Standard { rank, suit } => format!("{} of {}", rank.name(), suit.name()),
+            Face::Joker { red: true } => "red joker".to_string(),
+            Face::Joker { red: false } => "black joker".to_string(),
+            Face::Colored {
+                value,
+                color: Some(color),
+            } => format!("{} {}", color, value),
+            Face::Colored { value, color: None } => value.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(suit: Option<&str>, value: Option<&str>) -> wire::CardState {
+        wire::CardState {
+            suit: suit.map(str::to_string),
+            value: value.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn recognizes_faces() {
+        let face = |suit, value| Face::from_state(&state(suit, value));
+
+        assert_eq!(
+            face(Some("H"), Some("2")),
+            Some(Face::Standard {
+                rank: Rank::Number(2),
+                suit: Suit::Hearts
+            })
+        );
+        assert_eq!(
+            face(Some("spades"), Some("1")),
+            Some(Face::Standard {
+                rank: Rank::Ace,
+                suit: Suit::Spades
+            })
+        );
+        assert_eq!(
+            face(Some("D"), Some("joker")),
+            Some(Face::Joker { red: true })
+        );
+        assert_eq!(face(None, Some("JOKER")), Some(Face::Joker { red: false }));
+        assert_eq!(
+            face(Some("green"), Some("7")),
+            Some(Face::Colored {
+                value: "7".to_string(),
+                color: Some("green".to_string())
+            })
+        );
+        assert_eq!(face(Some("H"), None), None);
+        assert_eq!(face(None, None), None);
+    }
+
+    #[test]
+    fn colored_cards_use_the_color_property() {
+        let mut state = state(None, Some("+2"));
+        state.properties.insert(
+            COLOR_PROPERTY.to_string(),
+            wire::CardProperty::Color {
+                color: "#e02020".to_string(),
+            },
+        );
+        assert_eq!(
+            Face::from_state(&state),
+            Some(Face::Colored {
+                value: "+2".to_string(),
+                color: Some("#e02020".to_string())
+            })
+        );
+    }
+}
@@ -0,0 +1,657 @@
+//! Client-side model of a game, built from the messages sent by the server.
+
+use log::*;
+use std::collections::BTreeMap;
+
+use crate::wire;
+
+pub mod aria;
+pub mod faces;
+pub mod recording;
+
+/// Local copy of the state of a game. It is only modified by applying the
+/// messages received from the server, so that it can be rebuilt at any time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GameState {
+    pub components: BTreeMap<wire::ComponentId, wire::Component>,
+    pub layout: BTreeMap<wire::ComponentId, wire::ComponentPosition>,
+    pub awaited_actions: Vec<wire::AwaitedAction>,
+    /// The players who joined the game, in order.
+    pub players: Vec<Player>,
+    pub turn: Option<Turn>,
+    /// The players a spectator may watch the game as.
+    pub spectator_seats: Vec<String>,
+    pub started: bool,
+    pub winners: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    /// Player ID, which the other messages refer to the player by.
+    pub id: String,
+    /// Name of the player, to show.
+    pub name: String,
+    pub connected: bool,
+    pub score: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    /// Player ID of the player whose turn it is.
+    pub player_id: String,
+    /// Number of seconds the player has to play.
+    pub time_limit: Option<u32>,
+    /// Counts the turns, to tell two turns of the same player apart.
+    pub number: usize,
+}
+
+type StateChanged = bool;
+
+impl GameState {
+    /// Applies a message received from the server. Unknown messages, which are
+    /// most likely sent by a more recent server, are skipped.
+    pub fn apply(&mut self, message: &wire::Message) -> StateChanged {
+        match message {
+            wire::Message::PlayerConnected(player) => {
+                self.set_connected(player.id(), &player.username, true)
+            }
+            wire::Message::PlayerReconnected(player) => {
+                self.set_connected(player.id(), &player.username, true)
+            }
+            wire::Message::PlayerDisconnected(player) => {
+                self.set_connected(player.id(), &player.username, false)
+            }
+            wire::Message::PlayerLeft(player) => {
+                let count = self.players.len();
+                self.players.retain(|p| p.id != player.id());
+                self.players.len() != count
+            }
+            wire::Message::Spectating(spectating) => {
+                self.spectator_seats = spectating.seats.clone();
+                true
+            }
+            wire::Message::TurnChanged(turn) => {
+                for (player_id, score) in turn.scores.iter() {
+                    match self
+                        .players
+                        .iter_mut()
+                        .find(|player| &player.id == player_id)
+                    {
+                        Some(player) => player.score = Some(*score),
+                        None => warn!("Skipping score of unknown player: {:?}", player_id),
+                    }
+                }
+                self.turn = Some(Turn {
+                    player_id: turn.player_id.clone(),
+                    time_limit: turn.time_limit,
+                    number: self.turn.as_ref().map_or(1, |turn| turn.number + 1),
+                });
+                true
+            }
+            wire::Message::GameStarted => {
+                self.started = true;
+                true
+            }
+            wire::Message::GameFinished(finished) => {
+                self.awaited_actions.clear();
+                self.turn = None;
+                self.winners = Some(finished.winners.clone());
+                true
+            }
+            wire::Message::ActionAwaited(action_awaited) => {
+                self.awaited_actions = action_awaited
+                    .all_of
+                    .iter()
+                    .filter(|action| match action.unknown_type() {
+                        Some(r#type) => {
+                            warn!("Skipping awaited action of unknown type: {:?}", r#type);
+                            false
+                        }
+                        None => true,
+                    })
+                    .cloned()
+                    .collect();
+                true
+            }
+            wire::Message::InterfaceUpdate(interface) => {
+                for component in interface.components.iter() {
+                    self.layout.insert(component.id.clone(), component.position);
+                }
+                true
+            }
+            wire::Message::ComponentsUpdates(updates) => {
+                updates.updates.iter().fold(false, |changed, update| {
+                    self.apply_update(update) || changed
+                })
+            }
+            wire::Message::Unknown { r#type, .. } => {
+                warn!("Skipping message of unknown type: {:?}", r#type);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn set_connected(&mut self, player_id: &str, name: &str, connected: bool) -> StateChanged {
+        match self
+            .players
+            .iter_mut()
+            .find(|player| player.id == player_id)
+        {
+            Some(player) => {
+                let changed = player.connected != connected || player.name != name;
+                player.connected = connected;
+                player.name = name.to_string();
+                changed
+            }
+            None => {
+                self.players.push(Player {
+                    id: player_id.to_string(),
+                    name: name.to_string(),
+                    connected,
+                    score: None,
+                });
+                true
+            }
+        }
+    }
+
+    /// Returns the name of a player, or their ID if they are not known.
+    pub fn player_name<'a>(&'a self, player_id: &'a str) -> &'a str {
+        self.players
+            .iter()
+            .find(|player| player.id == player_id)
+            .map_or(player_id, |player| &player.name)
+    }
+
+    /// Returns the number of cards in the hands of a player.
+    pub fn card_count(&self, player_id: &str) -> usize {
+        self.components
+            .values()
+            .map(|component| match component {
+                wire::Component::Hand(hand) if hand.owner.as_deref() == Some(player_id) => {
+                    hand.cards.len()
+                }
+                _ => 0,
+            })
+            .sum()
+    }
+
+    fn apply_update(&mut self, update: &wire::ComponentUpdate) -> StateChanged {
+        match update {
+            wire::ComponentUpdate::Create { id, component } => {
+                if let Some(r#type) = component.unknown_type() {
+                    warn!("Skipping component {:?} of unknown type: {:?}", id, r#type);
+                    return false;
+                }
+                self.components.insert(id.clone(), component.clone());
+                true
+            }
+            wire::ComponentUpdate::Flip {
+                id,
+                visibility,
+                card,
+            } => match self.components.get_mut(id) {
+                Some(wire::Component::Card(current)) => {
+                    if let Some(card) = card {
+                        *current = (**card).clone();
+                    }
+                    current.visibility = *visibility;
+                    true
+                }
+                _ => {
+                    warn!("Cannot flip {:?}, which is not a card.", id);
+                    false
+                }
+            },
+            wire::ComponentUpdate::Unknown { r#type, .. } => {
+                warn!("Skipping component update of unknown type: {:?}", r#type);
+                false
+            }
+        }
+    }
+
+    /// Returns the hand holding the given card and its ID, if any.
+    pub fn hand_of(
+        &self,
+        card_id: &wire::ComponentId,
+    ) -> Option<(&wire::ComponentId, &wire::Hand)> {
+        self.components
+            .iter()
+            .find_map(|(id, component)| match component {
+                wire::Component::Hand(hand) if hand.cards.contains(card_id) => Some((id, hand)),
+                _ => None,
+            })
+    }
+
+    /// Whether the given player can see the face of a card. The visibility of
+    /// a card is restricted by the one of its hand, which also gives the card
+    /// its owner if the card doesn't have one.
+    pub fn is_face_visible(&self, card_id: &wire::ComponentId, player_id: &str) -> bool {
+        let card = match self.components.get(card_id) {
+            Some(wire::Component::Card(card)) => card,
+            _ => return false,
+        };
+        let hand = self.hand_of(card_id).map(|(_, hand)| hand);
+
+        let owner = card
+            .owner
+            .as_deref()
+            .or_else(|| hand.and_then(|hand| hand.owner.as_deref()));
+        let visibility = match hand {
+            Some(hand) => card.visibility.min(hand.visibility),
+            None => card.visibility,
+        };
+
+        let visible = match visibility {
+            wire::Visibility::Hidden => false,
+            wire::Visibility::Owner => owner == Some(player_id),
+            wire::Visibility::Everyone => true,
+        };
+        visible && card.has_identity()
+    }
+
+    /// Whether one of the awaited actions is to drag the given component.
+    pub fn is_draggable(&self, id: &wire::ComponentId) -> bool {
+        self.drop_targets(id).next().is_some()
+    }
+
+    /// Returns the components the given one is awaited to be dropped onto.
+    pub fn drop_targets<'a>(
+        &'a self,
+        source: &'a wire::ComponentId,
+    ) -> impl Iterator<Item = wire::ComponentId> + 'a {
+        self.awaited_actions
+            .iter()
+            .filter_map(move |action| match action {
+                wire::AwaitedAction::OnDrop {
+                    source_component,
+                    target_component,
+                } if source_component == source.as_str() => Some(target_component.into()),
+                _ => None,
+            })
+    }
+
+    /// Returns the awaited action performed by dropping a component onto
+    /// another one, if any.
+    pub fn drop_action(
+        &self,
+        source: &wire::ComponentId,
+        target: &wire::ComponentId,
+    ) -> Option<&wire::AwaitedAction> {
+        self.awaited_actions.iter().find(|action| match action {
+            wire::AwaitedAction::OnDrop {
+                source_component,
+                target_component,
+            } => source_component == source.as_str() && target_component == target.as_str(),
+            _ => false,
+        })
+    }
+
+    /// Returns the awaited `SelectMany` action the given component can be
+    /// selected for, if any.
+    pub fn selection_action(&self, id: &wire::ComponentId) -> Option<&wire::AwaitedAction> {
+        self.awaited_actions.iter().find(|action| match action {
+            wire::AwaitedAction::SelectMany {
+                among_components, ..
+            } => among_components
+                .iter()
+                .any(|component| component == id.as_str()),
+            _ => false,
+        })
+    }
+
+    /// Returns the awaited `SelectMany` action which the given components
+    /// fulfill, which may be one allowing to select nothing.
+    pub fn confirmed_selection(
+        &self,
+        selected: &[wire::ComponentId],
+    ) -> Option<&wire::AwaitedAction> {
+        self.awaited_actions.iter().find(|action| match action {
+            wire::AwaitedAction::SelectMany {
+                among_components,
+                min,
+                max,
+            } => {
+                (*min..=*max).contains(&selected.len())
+                    && selected.iter().all(|id| {
+                        among_components
+                            .iter()
+                            .any(|component| component == id.as_str())
+                    })
+            }
+            _ => false,
+        })
+    }
+
+    /// Returns the awaited action performed by clicking the given component.
+    pub fn click_action(&self, id: &wire::ComponentId) -> Option<&wire::AwaitedAction> {
+        self.awaited_actions.iter().find(|action| match action {
+            wire::AwaitedAction::OnClick { target_component } => target_component == id.as_str(),
+            _ => false,
+        })
+    }
+
+    /// Returns the components placed at the given position, in a stable order.
+    pub fn components_at(
+        &self,
+        position: wire::ComponentPosition,
+    ) -> impl Iterator<Item = &wire::ComponentId> {
+        self.layout
+            .iter()
+            .filter(move |(_, p)| **p == position)
+            .map(|(id, _)| id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(json: &str) -> wire::Message {
+        serde_json::from_str(json).expect("decoding failed")
+    }
+
+    #[test]
+    fn creates_and_places_components() {
+        let mut state = GameState::default();
+        assert!(state.apply(&decode(
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    { "type": "Create", "id": "hand", "component": { "type": "Hand", "cards": ["c1"] } },
+                    { "type": "Create", "id": "c1", "component": {
+                        "type": "Card", "name": "H2", "description": "",
+                        "front_image": null, "back_image": null,
+                        "state": { "suit": "H", "value": "2" }
+                    } }
+                ]
+            }"#
+        )));
+        assert!(state.apply(&decode(
+            r#"{"type":"INTERFACE_UPDATE","components":[{"id":"hand","position":"bottom"}]}"#
+        )));
+
+        assert_eq!(state.components.len(), 2);
+        match state.components.get(&"c1".into()) {
+            Some(wire::Component::Card(card)) => assert_eq!(card.name, "H2"),
+            other => panic!("expected a card, got {:?}", other),
+        }
+        assert_eq!(
+            state
+                .components_at(wire::ComponentPosition::Bottom)
+                .collect::<Vec<_>>(),
+            vec![&wire::ComponentId::from("hand")]
+        );
+    }
+
+    #[test]
+    fn skips_unknown_messages() {
+        let mut state = GameState::default();
+        state.apply(&decode(
+            r#"{"type":"ACTION_AWAITED","all_of":[{"type":"OnClick","target_component":"hand"}]}"#,
+        ));
+        let before = state.clone();
+
+        assert!(!state.apply(&decode(r#"{"type":"SHUFFLE","seed":42}"#)));
+        assert!(!state.apply(&decode(
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    { "type": "Destroy", "id": "hand" },
+                    { "type": "Create", "id": "deck", "component": { "type": "Deck" } }
+                ]
+            }"#
+        )));
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn tracks_players() {
+        let mut state = GameState::default();
+        let joined = decode(r#"{"type":"PLAYER_CONNECTED","message":"","username":"alice"}"#);
+        assert!(state.apply(&joined));
+        assert!(!state.apply(&joined));
+        state.apply(&decode(
+            r#"{"type":"PLAYER_CONNECTED","message":"","username":"bob"}"#,
+        ));
+        assert!(state.apply(&decode(
+            r#"{"type":"PLAYER_DISCONNECTED","username":"bob"}"#
+        )));
+        state.apply(&decode(
+            r#"{"type":"TURN_CHANGED","player_id":"alice","time_limit":30,"scores":{"bob":3}}"#,
+        ));
+        state.apply(&decode(r#"{"type":"TURN_CHANGED","player_id":"alice"}"#));
+
+        assert_eq!(
+            state.players,
+            vec![
+                Player {
+                    id: "alice".to_string(),
+                    name: "alice".to_string(),
+                    connected: true,
+                    score: None,
+                },
+                Player {
+                    id: "bob".to_string(),
+                    name: "bob".to_string(),
+                    connected: false,
+                    score: Some(3),
+                },
+            ]
+        );
+        assert_eq!(
+            state.turn,
+            Some(Turn {
+                player_id: "alice".to_string(),
+                time_limit: None,
+                number: 2,
+            })
+        );
+
+        let left = decode(r#"{"type":"PLAYER_LEFT","username":"bob","kicked":true}"#);
+        assert!(state.apply(&left));
+        assert!(!state.apply(&left));
+        assert_eq!(state.players.len(), 1);
+    }
+
+    #[test]
+    fn tracks_players_by_id() {
+        let mut state = GameState::default();
+        let joined = decode(
+            r#"{"type":"PLAYER_CONNECTED","message":"","player_id":"p1","username":"Alice"}"#,
+        );
+        assert!(state.apply(&joined));
+        assert!(!state.apply(&joined));
+        state.apply(&decode(
+            r#"{"type":"PLAYER_CONNECTED","message":"","player_id":"p2","username":"Bob"}"#,
+        ));
+        assert!(state.apply(&decode(
+            r#"{"type":"PLAYER_DISCONNECTED","player_id":"p2","username":"Bob"}"#
+        )));
+        state.apply(&decode(
+            r#"{"type":"TURN_CHANGED","player_id":"p1","time_limit":30,"scores":{"p2":3,"p3":1}}"#,
+        ));
+        state.apply(&decode(r#"{"type":"TURN_CHANGED","player_id":"p1"}"#));
+
+        assert_eq!(
+            state.players,
+            vec![
+                Player {
+                    id: "p1".to_string(),
+                    name: "Alice".to_string(),
+                    connected: true,
+                    score: None,
+                },
+                Player {
+                    id: "p2".to_string(),
+                    name: "Bob".to_string(),
+                    connected: false,
+                    score: Some(3),
+                },
+            ]
+        );
+        assert_eq!(
+            state.turn,
+            Some(Turn {
+                player_id: "p1".to_string(),
+                time_limit: None,
+                number: 2,
+            })
+        );
+        assert_eq!(state.player_name("p2"), "Bob");
+        assert_eq!(state.player_name("p3"), "p3");
+
+        let left =
+            decode(r#"{"type":"PLAYER_LEFT","player_id":"p2","username":"Bob","kicked":true}"#);
+        assert!(state.apply(&left));
+        assert!(!state.apply(&left));
+        assert_eq!(state.players.len(), 1);
+    }
+
+    #[test]
+    fn drop_actions() {
+        let mut state = GameState::default();
+        state.apply(&decode(
+            r#"{
+                "type": "ACTION_AWAITED",
+                "all_of": [
+                    { "type": "OnClick", "target_component": "deck" },
+                    { "type": "OnDrop", "source_component": "c1", "target_component": "pile" },
+                    { "type": "OnDrop", "source_component": "c1", "target_component": "hand2" },
+                    { "type": "OnDrop", "source_component": "c2", "target_component": "pile" }
+                ]
+            }"#,
+        ));
+
+        let c1 = wire::ComponentId::from("c1");
+        assert!(state.is_draggable(&c1));
+        assert!(!state.is_draggable(&"deck".into()));
+        assert_eq!(
+            state.drop_targets(&c1).collect::<Vec<_>>(),
+            vec!["pile".into(), "hand2".into()]
+        );
+        assert_eq!(
+            state.drop_action(&c1, &"pile".into()),
+            Some(&wire::AwaitedAction::OnDrop {
+                source_component: "c1".to_string(),
+                target_component: "pile".to_string(),
+            })
+        );
+        assert_eq!(state.drop_action(&c1, &"c2".into()), None);
+        assert_eq!(
+            state.click_action(&"deck".into()),
+            Some(&wire::AwaitedAction::OnClick {
+                target_component: "deck".to_string(),
+            })
+        );
+        assert_eq!(state.click_action(&c1), None);
+    }
+
+    #[test]
+    fn selection_actions() {
+        let mut state = GameState::default();
+        state.apply(&decode(
+            r#"{
+                "type": "ACTION_AWAITED",
+                "all_of": [
+                    { "type": "SelectMany", "among_components": ["c1", "c2"], "min": 1, "max": 1 }
+                ]
+            }"#,
+        ));
+
+        assert_eq!(
+            state.selection_action(&"c2".into()),
+            state.awaited_actions.first()
+        );
+        assert_eq!(state.selection_action(&"c3".into()), None);
+
+        assert_eq!(
+            state.confirmed_selection(&["c1".into()]),
+            state.awaited_actions.first()
+        );
+        assert_eq!(state.confirmed_selection(&[]), None);
+        assert_eq!(state.confirmed_selection(&["c1".into(), "c2".into()]), None);
+
+        state.apply(&decode(
+            r#"{
+                "type": "ACTION_AWAITED",
+                "all_of": [
+                    { "type": "SelectMany", "among_components": ["c1", "c2"], "min": 0, "max": 2 }
+                ]
+            }"#,
+        ));
+        assert_eq!(
+            state.confirmed_selection(&[]),
+            state.awaited_actions.first()
+        );
+    }
+
+    #[test]
+    fn card_visibility() {
+        let mut state = GameState::default();
+        state.apply(&decode(
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    { "type": "Create", "id": "hand", "component": {
+                        "type": "Hand", "cards": ["c1", "c2"], "owner": "p1", "visibility": "owner"
+                    } },
+                    { "type": "Create", "id": "c1", "component": {
+                        "type": "Card", "name": "H2", "front_image": null, "back_image": null
+                    } },
+                    { "type": "Create", "id": "c2", "component": {
+                        "type": "Card", "front_image": null, "back_image": null, "visibility": "hidden"
+                    } },
+                    { "type": "Create", "id": "c3", "component": {
+                        "type": "Card", "name": "S1", "front_image": null, "back_image": null
+                    } }
+                ]
+            }"#,
+        ));
+
+        assert!(state.is_face_visible(&"c1".into(), "p1"));
+        assert!(!state.is_face_visible(&"c1".into(), "p2"));
+        assert!(!state.is_face_visible(&"c2".into(), "p1"));
+        assert!(state.is_face_visible(&"c3".into(), "p2"));
+        assert!(!state.is_face_visible(&"hand".into(), "p1"));
+        assert_eq!(state.card_count("p1"), 2);
+        assert_eq!(state.card_count("p2"), 0);
+    }
+
+    #[test]
+    fn flips_cards() {
+        let mut state = GameState::default();
+        state.apply(&decode(
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    { "type": "Create", "id": "c1", "component": {
+                        "type": "Card", "front_image": null, "back_image": null, "visibility": "hidden"
+                    } }
+                ]
+            }"#,
+        ));
+        assert!(!state.is_face_visible(&"c1".into(), "p1"));
+
+        assert!(state.apply(&decode(
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    { "type": "Flip", "id": "c1", "visibility": "everyone", "card": {
+                        "name": "H2", "front_image": null, "back_image": null
+                    } }
+                ]
+            }"#,
+        )));
+        assert!(state.is_face_visible(&"c1".into(), "p1"));
+
+        assert!(state.apply(&decode(
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [{ "type": "Flip", "id": "c1", "visibility": "hidden" }]
+            }"#,
+        )));
+        assert!(!state.is_face_visible(&"c1".into(), "p1"));
+    }
+}
@@ -0,0 +1,145 @@
+//! Recordings of the messages exchanged during a game session, exported as
+//! JSONL files to replay the game without a server.
+//!
+//! The first line of a file holds the header of the recording, and each of
+//! the following lines one of the messages, in the order they were sent or
+//! received.
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::GameState;
+use crate::jsonl;
+use crate::wire;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RecordingHeader {
+    pub game_id: String,
+    /// The player the game was seen by, to replay what they saw.
+    pub player_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordedMessage {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+    pub direction: Direction,
+    pub message: wire::Message,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    pub header: RecordingHeader,
+    pub messages: Vec<RecordedMessage>,
+}
+
+impl Recording {
+    pub fn new(game_id: impl Into<String>, player_id: impl Into<String>) -> Self {
+        Recording {
+            header: RecordingHeader {
+                game_id: game_id.into(),
+                player_id: player_id.into(),
+            },
+            messages: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, timestamp: f64, direction: Direction, message: wire::Message) {
+        self.messages.push(RecordedMessage {
+            timestamp,
+            direction,
+            message,
+        });
+    }
+
+    pub fn to_jsonl(&self) -> String {
+        jsonl::write(&self.header, &self.messages)
+    }
+
+    pub fn from_jsonl(text: &str) -> Result<Self, String> {
+        let (header, messages) = jsonl::read(text, "recording", "header")?;
+        Ok(Recording { header, messages })
+    }
+
+    /// Rebuilds the state of the game once the given number of messages were
+    /// exchanged.
+    pub fn state_after(&self, count: usize) -> GameState {
+        let mut state = GameState::default();
+        for recorded in self.messages.iter().take(count) {
+            if recorded.direction == Direction::Received {
+                state.apply(&recorded.message);
+            }
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_jsonl() {
+        let mut recording = Recording::new("round", "p1");
+        recording.record(1000.0, Direction::Received, wire::Message::GameStarted);
+        recording.record(
+            1500.5,
+            Direction::Sent,
+            wire::SendChatMessage {
+                text: "gl".to_string(),
+                target: None,
+            }
+            .into(),
+        );
+
+        let jsonl = recording.to_jsonl();
+        assert_eq!(
+            jsonl,
+            concat!(
+                r#"{"game_id":"round","player_id":"p1"}"#,
+                "\n",
+                r#"{"timestamp":1000.0,"direction":"received","message":{"type":"GAME_STARTED"}}"#,
+                "\n",
+                r#"{"timestamp":1500.5,"direction":"sent","message":{"type":"SEND_CHAT_MESSAGE","text":"gl"}}"#,
+                "\n",
+            )
+        );
+        assert_eq!(Recording::from_jsonl(&jsonl), Ok(recording));
+    }
+
+    #[test]
+    fn reports_invalid_lines() {
+        assert_eq!(
+            Recording::from_jsonl(""),
+            Err("The recording is empty.".to_string())
+        );
+        let error =
+            Recording::from_jsonl("{\"game_id\":\"g\",\"player_id\":\"p\"}\n\n{}").unwrap_err();
+        assert!(error.starts_with("Line 3: "), "{}", error);
+    }
+
+    #[test]
+    fn replays_received_messages() {
+        let mut recording = Recording::new("round", "p1");
+        recording.record(0.0, Direction::Received, wire::Message::GameStarted);
+        recording.record(
+            1.0,
+            Direction::Received,
+            wire::GameFinished {
+                winners: vec!["p1".to_string()],
+            }
+            .into(),
+        );
+
+        assert!(!recording.state_after(0).started);
+        assert!(recording.state_after(1).started);
+        assert_eq!(recording.state_after(1).winners, None);
+        assert!(recording.state_after(5).winners.is_some());
+    }
+}
@@ -9,6 +9,9 @@ pub struct PureTextInputField {
     pub oninput: Callback<yew::events::InputData>,
     #[prop_or(false)]
     pub disabled: bool,
+    /// Tells what is wrong with the value, below the field.
+    #[prop_or(None)]
+    pub error: Option<String>,
 }
 pub type TextInputField = Pure<PureTextInputField>;
 
@@ -19,21 +22,30 @@ impl PureComponent for PureTextInputField {
                 <label class="label">{ &self.label }</label>
                 <div class="control has-icons-left">
                     <input
-                        class="input" type="text"
+                        class=("input", if self.error.is_some() { "is-danger" } else { "" })
+                        type="text"
                         placeholder=&self.placeholder
                         value=&self.value
                         oninput=&self.oninput
                         disabled=self.disabled
+                        aria-invalid=if self.error.is_some() { "true" } else { "false" }
                         />
                     <span class="icon is-small is-left">
                         <i class="fas fa-server"></i>
                     </span>
                 </div>
+                {
+                    match &self.error {
+                        Some(error) => html! { <p class="help is-danger">{ error }</p> },
+                        None => html! {},
+                    }
+                }
             </div>
         }
     }
 }
 
+#[cfg(feature = "debug-console")]
 #[derive(Clone, Properties, PartialEq)]
 pub struct PureTextAreaField {
     pub label: String,
@@ -44,9 +56,14 @@ pub struct PureTextAreaField {
     pub disabled: bool,
     #[prop_or(None)]
     pub class: Option<String>,
+    /// Shown below the field, in the color given by `class`.
+    #[prop_or(None)]
+    pub help: Option<String>,
 }
+#[cfg(feature = "debug-console")]
 pub type TextAreaField = Pure<PureTextAreaField>;
 
+#[cfg(feature = "debug-console")]
 impl PureComponent for PureTextAreaField {
     fn render(&self) -> Html {
         html! {
@@ -61,6 +78,12 @@ impl PureComponent for PureTextAreaField {
                         disabled=self.disabled
                     ></textarea>
                 </div>
+                {
+                    match &self.help {
+                        Some(help) => html! { <p class=("help", &self.class)>{ help }</p> },
+                        None => html! {},
+                    }
+                }
             </div>
         }
     }
@@ -81,3 +104,41 @@ impl PureComponent for PureProgressBar {
         }
     }
 }
+
+/// A modal dialog asking to confirm an action which can't be undone.
+#[derive(Clone, Properties, PartialEq)]
+pub struct PureConfirmDialog {
+    pub title: String,
+    pub message: String,
+    pub confirm_label: String,
+    pub on_confirm: Callback<()>,
+    pub on_cancel: Callback<()>,
+}
+pub type ConfirmDialog = Pure<PureConfirmDialog>;
+
+impl PureComponent for PureConfirmDialog {
+    fn render(&self) -> Html {
+        html! {
+            <div class="modal is-active" role="alertdialog" aria-modal="true" aria-label=&self.title>
+                <div class="modal-background" onclick=self.on_cancel.reform(|_| ())></div>
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ &self.title }</p>
+                        <button class="delete" aria-label="Cancel" onclick=self.on_cancel.reform(|_| ())></button>
+                    </header>
+                    <section class="modal-card-body">
+                        <p>{ &self.message }</p>
+                    </section>
+                    <footer class="modal-card-foot">
+                        <button class="button is-danger" onclick=self.on_confirm.reform(|_| ())>
+                            { &self.confirm_label }
+                        </button>
+                        <button class="button" onclick=self.on_cancel.reform(|_| ())>
+                            { "Cancel" }
+                        </button>
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! JSON Lines files whose first line holds a header, telling where the items
+//! on the following lines come from.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Writes the header and the items, one per line. Values which can't be
+/// serialized are logged and left out.
+pub fn write<'a, H, T>(header: &H, items: impl IntoIterator<Item = &'a T>) -> String
+where
+    H: Serialize,
+    T: Serialize + 'a,
+{
+    let header = serde_json::to_string(header);
+    let items = items.into_iter().map(serde_json::to_string);
+    std::iter::once(header)
+        .chain(items)
+        .filter_map(|line| line.map_err(|e| log::error!("{}", e)).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// Reads the header and the items, skipping the blank lines. The errors name
+/// the file and its header, e.g. "recording" and "header", and give the line
+/// numbers.
+pub fn read<H, T>(text: &str, file: &str, header: &str) -> Result<(H, Vec<T>), String>
+where
+    H: DeserializeOwned,
+    T: DeserializeOwned,
+{
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let header = match lines.next() {
+        Some((_, line)) => serde_json::from_str(line)
+            .map_err(|e| format!("Invalid {} on line 1: {}", header, e))?,
+        None => return Err(format!("The {} is empty.", file)),
+    };
+    let items = lines
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| format!("Line {}: {}", index + 1, e))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((header, items))
+}
@@ -4,10 +4,13 @@ mod agents;
 mod app;
 mod components;
 mod constants;
+mod game;
 mod html;
+mod jsonl;
 mod pages;
 mod routes;
 mod services;
+mod settings;
 mod state;
 mod wire;
 
@@ -10,6 +10,7 @@ use yew_router::route::Route;
 use crate::agents::game_ws_mgr::*;
 use crate::html::*;
 use crate::routes::AppRoute;
+use crate::services::api;
 use crate::services::game_server::*;
 
 pub struct JoiningGame {
@@ -99,6 +100,16 @@ impl Component for JoiningGame {
         let current_step = std::mem::replace(&mut self.step, JoinStep::Failed);
         self.step = match (current_step, msg) {
             (JoinStep::WantToJoinGame, Msg::JoinRound) => {
+                // The route may have been written by hand.
+                let valid = api::validate_game_id(&self.game_id)
+                    .and_then(|_| api::validate_username(&self.username));
+                if let Err(error) = valid {
+                    self.step = JoinStep::JoinFailed {
+                        player_id: None,
+                        error,
+                    };
+                    return true;
+                }
                 self.current_task = Some(Box::new(self.game_server.join_round(
                     &self.game_id,
                     &self.username,
@@ -113,6 +124,7 @@ impl Component for JoiningGame {
                     .send(GameWsRequest::JoinRound(GameWsConnectionInfo {
                         game_id: self.game_id.clone(),
                         player_id: player_id.clone(),
+                        spectator: None,
                     }));
                 JoinStep::JoinedGameWebSocketPending { player_id }
             }
@@ -145,11 +157,7 @@ impl Component for JoiningGame {
             (step, Msg::GameWsResponse(_)) => step,
 
             (JoinStep::JoinedGameWithWebSocket { player_id, .. }, Msg::SuccessfullyJoined) => {
-                let route: Route = AppRoute::PlayGame {
-                    game_id: self.game_id.clone(),
-                    player_id,
-                }
-                .into();
+                let route: Route = AppRoute::play_game(&self.game_id, &player_id).into();
                 RouteAgentDispatcher::new().send(RouteRequest::ChangeRoute(route));
                 JoinStep::WaitingRedirect
             }
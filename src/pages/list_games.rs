@@ -2,6 +2,7 @@ use yew::prelude::*;
 
 use crate::html::*;
 use crate::routes::*;
+use crate::services::api;
 
 pub struct ListGames {
     link: ComponentLink<Self>,
@@ -41,10 +42,17 @@ impl Component for ListGames {
     }
 
     fn view(&self) -> Html {
-        let join_route: AppRoute = AppRoute::JoinGame {
-            game_id: self.game_id.clone(),
-            username: self.username.clone(),
+        let join_route = AppRoute::join_game(&self.game_id, &self.username);
+        let watch_route = AppRoute::watch_game(&self.game_id, &self.username);
+
+        // Empty fields are not reported as errors before being filled.
+        let error = |value: &str, validate: fn(&str) -> Result<(), String>| {
+            validate(value).err().filter(|_| !value.is_empty())
         };
+        let game_id_error = error(&self.game_id, api::validate_game_id);
+        let username_error = error(&self.username, api::validate_username);
+        let invalid = api::validate_game_id(&self.game_id).is_err()
+            || api::validate_username(&self.username).is_err();
 
         let game_id_changed = self
             .link
@@ -63,19 +71,27 @@ impl Component for ListGames {
                     placeholder="Enter the ID of the round to join."
                     value=&self.game_id
                     oninput=game_id_changed
+                    error=game_id_error
                     />
                 <TextInputField
                     label="Player name"
                     placeholder="Enter your player name here."
                     value=&self.username
                     oninput=username_changed
+                    error=username_error
                     />
 
-                <div class="control">
-                    // TODO: Disable this when empty fields
-                    <NavBtn classes="button is-primary" route=join_route>
-                        { "Join game" }
-                    </NavBtn>
+                <div class="field is-grouped">
+                    <div class="control">
+                        <NavBtn classes="button is-primary" route=join_route disabled=invalid>
+                            { "Join game" }
+                        </NavBtn>
+                    </div>
+                    <div class="control">
+                        <NavBtn classes="button is-light" route=watch_route disabled=invalid>
+                            { "Watch game" }
+                        </NavBtn>
+                    </div>
                 </div>
             </>
         }
@@ -4,6 +4,8 @@ pub mod joining_game;
 pub mod list_games;
 pub mod not_found;
 pub mod play_game;
+pub mod replay;
+pub mod settings;
 
 pub use create_game::{CreateGame, Msg as CreateGameMsg};
 pub use index::{Index, Msg as IndexMsg};
@@ -11,3 +13,5 @@ pub use joining_game::{JoiningGame, Msg as JoiningGameMsg};
 pub use list_games::{ListGames, Msg as ListGamesMsg};
 pub use not_found::{Msg as NotFoundMsg, NotFound};
 pub use play_game::{Msg as PlayGameMsg, PlayGame};
+pub use replay::{Msg as ReplayMsg, Replay};
+pub use settings::{Msg as SettingsMsg, SettingsPage};
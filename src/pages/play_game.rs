@@ -1,18 +1,47 @@
+use anyhow::Result;
 use derive_more::From;
+use std::rc::Rc;
 use yew::prelude::*;
+use yew_router::agent::{RouteAgentDispatcher, RouteRequest};
+use yew_router::route::Route;
 use yewtil::NeqAssign;
 
 use crate::agents::game_mgr::*;
 use crate::agents::game_ws_mgr::{GameWsConnectionInfo, WebSocketStatus};
 use crate::agents::notifications::*;
+use crate::agents::settings_bus::SettingsBus;
+use crate::components::{Announcer, Chat, Roster, Table};
+use crate::game::GameState;
+use crate::html::ConfirmDialog;
+use crate::routes::AppRoute;
+use crate::services::files;
+use crate::services::game_server::*;
+use crate::settings::Settings;
+use crate::wire;
+
+const JSONL_MIME_TYPE: &str = "application/x-ndjson";
 
 pub struct PlayGame {
     link: ComponentLink<Self>,
     notification_bus: Dispatcher<NotificationBus>,
 
     ws_status: WebSocketStatus,
+    game: Rc<GameState>,
+    /// The player a spectator watches the game as.
+    seat: Option<String>,
+    /// The component pointed out by the developer tools.
+    highlighted: Option<wire::ComponentId>,
+    /// Player ID of the creator of the round, once known.
+    created_by: Option<String>,
+    /// The action waiting for the player to confirm it.
+    confirmation: Option<Confirmation>,
+
+    settings: Settings,
+    _settings_bus: Box<dyn Bridge<SettingsBus>>,
 
     game_mgr_agent: Box<dyn Bridge<GameMgr>>,
+    game_server: GameServerService,
+    _round_task: Option<FetchTask>,
 
     props: Props,
 }
@@ -20,7 +49,12 @@ pub struct PlayGame {
 #[derive(Properties, Clone, Debug, PartialEq)]
 pub struct Props {
     pub game_id: String,
+    /// Player ID of the player, or name of the spectator when `spectator` is
+    /// set.
     pub player_id: String,
+    /// Watches the game without playing.
+    #[prop_or_default]
+    pub spectator: bool,
 }
 
 #[derive(From, Debug)]
@@ -32,11 +66,26 @@ pub enum Msg {
 #[derive(Debug)]
 pub enum Command {
     Update,
+    PerformAction(wire::PerformAction),
+    WatchSeat(Option<String>),
+    DownloadRecording,
+    AskConfirmation(Confirmation),
+    Confirm,
+    CancelConfirmation,
 }
 
 #[derive(Debug)]
 pub enum Event {
     GameMgrMessage(GameMgrResponse),
+    RoundFetched(Result<RoundResponse>),
+    SettingsChanged(Settings),
+}
+
+/// Actions asking for a confirmation, since they can't be undone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Confirmation {
+    LeaveRound,
+    KickPlayer(String),
 }
 
 impl NotificationSender for PlayGame {
@@ -52,13 +101,25 @@ impl Component for PlayGame {
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
         link.send_message(Command::Update);
         let game_mgr_msg_callback = link.callback(Event::GameMgrMessage);
+        let settings_bus = SettingsBus::bridge(link.callback(Event::SettingsChanged));
+
         PlayGame {
             link,
             notification_bus: NotificationBus::dispatcher(),
 
             ws_status: WebSocketStatus::NotConnected,
+            game: Rc::new(GameState::default()),
+            seat: None,
+            highlighted: None,
+            created_by: None,
+            confirmation: None,
+
+            settings: Settings::load(),
+            _settings_bus: settings_bus,
 
             game_mgr_agent: GameMgr::bridge(game_mgr_msg_callback),
+            game_server: GameServerService::new(),
+            _round_task: None,
 
             props,
         }
@@ -76,30 +137,260 @@ impl Component for PlayGame {
         match msg {
             Msg::Command(command) => match command {
                 Command::Update => {
-                    self.game_mgr_agent.send(GameMgrRequest::EnsureConnected(
+                    let props = &self.props;
+                    let info = if props.spectator {
                         GameWsConnectionInfo {
-                            game_id: self.props.game_id.clone(),
-                            player_id: self.props.player_id.clone(),
-                        },
-                    ));
+                            game_id: props.game_id.clone(),
+                            player_id: String::new(),
+                            spectator: Some(props.player_id.clone()),
+                        }
+                    } else {
+                        GameWsConnectionInfo {
+                            game_id: props.game_id.clone(),
+                            player_id: props.player_id.clone(),
+                            spectator: None,
+                        }
+                    };
+                    self.game_mgr_agent
+                        .send(GameMgrRequest::EnsureConnected(info));
+                    self._round_task =
+                        Some(self.game_server.get_round(
+                            &self.props.game_id,
+                            self.link.callback(Event::RoundFetched),
+                        ));
+                    false
+                }
+                Command::PerformAction(action) => {
+                    if !self.props.spectator {
+                        self.game_mgr_agent
+                            .send(GameMgrRequest::PerformAction(action));
+                    }
+                    false
+                }
+                Command::DownloadRecording => {
+                    self.game_mgr_agent.send(GameMgrRequest::GetRecording);
                     false
                 }
+                Command::WatchSeat(seat) => {
+                    self.game_mgr_agent
+                        .send(GameMgrRequest::WatchSeat(seat.clone()));
+                    self.seat.neq_assign(seat)
+                }
+                Command::AskConfirmation(confirmation) => {
+                    self.confirmation.neq_assign(Some(confirmation))
+                }
+                Command::CancelConfirmation => self.confirmation.neq_assign(None),
+                Command::Confirm => {
+                    match self.confirmation.take() {
+                        Some(Confirmation::LeaveRound) => {
+                            self.game_mgr_agent.send(GameMgrRequest::LeaveRound);
+                            self.notify_info("You left the round.");
+                            go_to(AppRoute::ListGames);
+                        }
+                        Some(Confirmation::KickPlayer(player_id)) => {
+                            self.game_mgr_agent
+                                .send(GameMgrRequest::KickPlayer(player_id));
+                        }
+                        None => {}
+                    }
+                    true
+                }
             },
             Msg::Event(event) => match event {
                 Event::GameMgrMessage(game_mgr_msg) => match game_mgr_msg {
                     GameMgrResponse::WebSocketStatusChanged(status) => {
                         self.ws_status.neq_assign(status)
                     }
+                    GameMgrResponse::GameStateChanged(mut game) => {
+                        // Spectators can't play, whatever the server says.
+                        if self.props.spectator && !game.awaited_actions.is_empty() {
+                            Rc::make_mut(&mut game).awaited_actions.clear();
+                        }
+                        self.game.neq_assign(game)
+                    }
+                    GameMgrResponse::Recording(recording) => {
+                        let filename = format!("game-{}.jsonl", recording.header.game_id);
+                        let jsonl = recording.to_jsonl();
+                        if let Err(e) = files::download(&filename, JSONL_MIME_TYPE, &jsonl) {
+                            self.notify_error(format!("Failed to download the recording: {}", e));
+                        }
+                        false
+                    }
+                    GameMgrResponse::Highlighted(id) => self.highlighted.neq_assign(id),
+                    // The reason was told to the player by the game manager.
+                    GameMgrResponse::SessionEnded => {
+                        go_to(AppRoute::ListGames);
+                        false
+                    }
+                    GameMgrResponse::Announcement(_) | GameMgrResponse::ChatMessageReceived(_) => {
+                        false
+                    }
                 },
+                Event::RoundFetched(round) => {
+                    self._round_task = None;
+                    match round {
+                        Ok(round) => self.created_by.neq_assign(Some(round.created_by)),
+                        Err(e) => {
+                            log::warn!("Failed to get the round: {}", e);
+                            false
+                        }
+                    }
+                }
+                Event::SettingsChanged(settings) => self.settings.neq_assign(settings),
             },
         }
     }
 
     fn view(&self) -> Html {
         html! {
-            <>
-                { "Play game" }
-            </>
+            <div class="columns">
+                <Announcer />
+                <div class="column">
+                    { self.render_spectator_bar() }
+                    <Table
+                        state=self.game.clone()
+                        player_id=self.seat.as_ref().unwrap_or(&self.props.player_id)
+                        back_theme=self.settings.back_theme.clone()
+                        animations=self.settings.animations.clone()
+                        key_bindings=self.settings.key_bindings.clone()
+                        highlighted=self.highlighted.clone()
+                        on_action=self.link.callback(Command::PerformAction)
+                        />
+                </div>
+                <div class="column is-one-quarter">
+                    <Roster
+                        state=self.game.clone()
+                        player_id=&self.props.player_id
+                        on_kick=self.kick_callback()
+                        />
+                    <Chat
+                        player_id=&self.props.player_id
+                        players=self.game.players.clone()
+                        />
+                    <button
+                        class="button is-small is-light game-recording-download"
+                        onclick=self.link.callback(|_| Command::DownloadRecording)
+                        >
+                        { "Download the recording" }
+                    </button>
+                    { self.render_leave_button() }
+                </div>
+                { self.render_confirmation() }
+            </div>
+        }
+    }
+
+    fn destroy(&mut self) {
+        self.game_mgr_agent.send(GameMgrRequest::Leave);
+    }
+}
+
+impl PlayGame {
+    /// Lets the creator of the round remove the other players.
+    fn kick_callback(&self) -> Option<Callback<String>> {
+        if self.props.spectator || self.created_by.as_ref() != Some(&self.props.player_id) {
+            return None;
+        }
+        Some(
+            self.link.callback(|player_id| {
+                Command::AskConfirmation(Confirmation::KickPlayer(player_id))
+            }),
+        )
+    }
+
+    fn render_leave_button(&self) -> Html {
+        if self.props.spectator {
+            return html! {};
+        }
+        html! {
+            <button
+                class="button is-small is-danger is-light game-leave-round"
+                onclick=self.link.callback(|_| Command::AskConfirmation(Confirmation::LeaveRound))
+                >
+                { "Leave the round" }
+            </button>
         }
     }
+
+    fn render_confirmation(&self) -> Html {
+        let (title, message, confirm_label) = match &self.confirmation {
+            Some(Confirmation::LeaveRound) => (
+                "Leave the round?".to_string(),
+                "Your seat will be given up, and you won't be able to come back to this round."
+                    .to_string(),
+                "Leave",
+            ),
+            Some(Confirmation::KickPlayer(player_id)) => {
+                let name = self.game.player_name(player_id);
+                (
+                    format!("Remove {}?", name),
+                    format!(
+                        "{} will be removed from the round, and won't be able to come back.",
+                        name
+                    ),
+                    "Remove",
+                )
+            }
+            None => return html! {},
+        };
+        html! {
+            <ConfirmDialog
+                title=title
+                message=message
+                confirm_label=confirm_label
+                on_confirm=self.link.callback(|_| Command::Confirm)
+                on_cancel=self.link.callback(|_| Command::CancelConfirmation)
+                />
+        }
+    }
+
+    /// Tells that the game is only watched, and lets the spectator pick the
+    /// player to watch the game as, if the server allows it.
+    fn render_spectator_bar(&self) -> Html {
+        if !self.props.spectator {
+            return html! {};
+        }
+
+        let render_seat = |seat: Option<&String>| {
+            let is_active = self.seat.as_ref() == seat;
+            let onclick = {
+                let seat = seat.cloned();
+                self.link
+                    .callback(move |_| Command::WatchSeat(seat.clone()))
+            };
+            html! {
+                <button
+                    class=("button is-small", if is_active { "is-info is-selected" } else { "" })
+                    aria-pressed=if is_active { "true" } else { "false" }
+                    onclick=onclick
+                    >
+                    { seat.map_or("Public view", String::as_str) }
+                </button>
+            }
+        };
+        let seats = &self.game.spectator_seats;
+        let switcher = if seats.is_empty() {
+            html! {}
+        } else {
+            html! {
+                <div class="buttons has-addons">
+                    { render_seat(None) }
+                    { for seats.iter().map(|seat| render_seat(Some(seat))) }
+                </div>
+            }
+        };
+
+        html! {
+            <div class="level game-spectator-bar">
+                <div class="level-left">
+                    <span class="tag is-info is-medium">{ "Spectating" }</span>
+                </div>
+                <div class="level-right">{ switcher }</div>
+            </div>
+        }
+    }
+}
+
+fn go_to(route: AppRoute) {
+    RouteAgentDispatcher::<()>::new().send(RouteRequest::ChangeRoute(Route::from(route)));
 }
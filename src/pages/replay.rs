@@ -0,0 +1,410 @@
+use std::rc::Rc;
+use std::time::Duration;
+use yew::prelude::*;
+use yew::services::reader::{FileData, ReaderService, ReaderTask};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+
+use crate::agents::notifications::*;
+use crate::components::{Roster, Table};
+use crate::game::recording::{Direction, Recording};
+use crate::game::GameState;
+use crate::settings::Settings;
+
+/// The playback speeds, as multipliers of the recorded pace.
+const SPEEDS: [f64; 4] = [0.5, 1.0, 2.0, 4.0];
+
+/// The longest wait between two messages while playing, so that idle moments
+/// of the game don't stall the replay.
+const MAX_DELAY_MS: f64 = 2000.0;
+
+pub struct Replay {
+    link: ComponentLink<Self>,
+    notification_bus: Dispatcher<NotificationBus>,
+
+    recording: Option<Recording>,
+    /// The number of recorded messages already replayed.
+    position: usize,
+    game: Rc<GameState>,
+
+    playing: bool,
+    speed: f64,
+
+    settings: Settings,
+
+    reader: ReaderService,
+    _reader_task: Option<ReaderTask>,
+    _play_task: Option<TimeoutTask>,
+}
+
+pub enum Msg {
+    FileSelected(web_sys::File),
+    FileLoaded(FileData),
+    Play,
+    Pause,
+    Tick,
+    StepForward,
+    StepBack,
+    Seek(usize),
+    SetSpeed(f64),
+    Ignore,
+}
+
+impl NotificationSender for Replay {
+    fn notification_bus(&mut self) -> &mut Dispatcher<NotificationBus> {
+        &mut self.notification_bus
+    }
+}
+
+impl Component for Replay {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Replay {
+            link,
+            notification_bus: NotificationBus::dispatcher(),
+
+            recording: None,
+            position: 0,
+            game: Rc::new(GameState::default()),
+
+            playing: false,
+            speed: 1.0,
+
+            settings: Settings::load(),
+
+            reader: ReaderService::new(),
+            _reader_task: None,
+            _play_task: None,
+        }
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::FileSelected(file) => {
+                let callback = self.link.callback(Msg::FileLoaded);
+                match self.reader.read_file(file, callback) {
+                    Ok(task) => self._reader_task = Some(task),
+                    Err(e) => self.notify_error(format!("Failed to read the file: {}", e)),
+                }
+                false
+            }
+            Msg::FileLoaded(file) => {
+                self._reader_task = None;
+                let content = String::from_utf8_lossy(&file.content);
+                match Recording::from_jsonl(&content) {
+                    Ok(recording) => {
+                        self.recording = Some(recording);
+                        self.pause();
+                        self.position = 0;
+                        self.game = Rc::new(GameState::default());
+                    }
+                    Err(e) => {
+                        self.notify_error(format!("Failed to load {}: {}", file.name, e));
+                    }
+                }
+                true
+            }
+            Msg::Play => {
+                if self.position >= self.len() {
+                    self.seek(0);
+                }
+                self.playing = true;
+                self.schedule_next();
+                true
+            }
+            Msg::Pause => {
+                self.pause();
+                true
+            }
+            Msg::Tick => {
+                self.seek(self.position + 1);
+                if self.position < self.len() {
+                    self.schedule_next();
+                } else {
+                    self.pause();
+                }
+                true
+            }
+            Msg::StepForward => {
+                self.pause();
+                self.seek(self.position + 1);
+                true
+            }
+            Msg::StepBack => {
+                self.pause();
+                self.seek(self.position.saturating_sub(1));
+                true
+            }
+            Msg::Seek(position) => {
+                self.seek(position);
+                if self.playing {
+                    self.schedule_next();
+                }
+                true
+            }
+            Msg::SetSpeed(speed) => {
+                self.speed = speed;
+                if self.playing {
+                    self.schedule_next();
+                }
+                true
+            }
+            Msg::Ignore => false,
+        }
+    }
+
+    fn view(&self) -> Html {
+        let on_file_change = self.link.callback(|event: ChangeData| match event {
+            ChangeData::Files(files) => match files.get(0) {
+                Some(file) => Msg::FileSelected(file),
+                None => Msg::Ignore,
+            },
+            _ => Msg::Ignore,
+        });
+
+        html! {
+            <>
+                <div class="file game-replay-file">
+                    <label class="file-label">
+                        <input
+                            class="file-input"
+                            type="file"
+                            accept=".jsonl,application/x-ndjson"
+                            onchange=on_file_change
+                            />
+                        <span class="file-cta">
+                            <span class="file-label">{ "Open a recording…" }</span>
+                        </span>
+                    </label>
+                </div>
+                { self.render_replay() }
+            </>
+        }
+    }
+}
+
+impl Replay {
+    fn len(&self) -> usize {
+        self.recording
+            .as_ref()
+            .map_or(0, |recording| recording.messages.len())
+    }
+
+    fn pause(&mut self) {
+        self.playing = false;
+        self._play_task = None;
+    }
+
+    /// Moves to the given position, replaying the messages through the game
+    /// state as the live game would.
+    fn seek(&mut self, position: usize) {
+        let recording = match &self.recording {
+            Some(recording) => recording,
+            None => return,
+        };
+        let position = position.min(recording.messages.len());
+        let mut game = if position >= self.position {
+            let mut game = (*self.game).clone();
+            for recorded in &recording.messages[self.position..position] {
+                if recorded.direction == Direction::Received {
+                    game.apply(&recorded.message);
+                }
+            }
+            game
+        } else {
+            recording.state_after(position)
+        };
+        // The replay only shows the game, nothing can be played.
+        game.awaited_actions.clear();
+        self.position = position;
+        self.game = Rc::new(game);
+    }
+
+    /// Waits for the time between the last replayed message and the next one.
+    fn schedule_next(&mut self) {
+        let messages = match &self.recording {
+            Some(recording) => &recording.messages,
+            None => return,
+        };
+        let delay = match (
+            self.position.checked_sub(1).and_then(|i| messages.get(i)),
+            messages.get(self.position),
+        ) {
+            (Some(last), Some(next)) => ((next.timestamp - last.timestamp) / self.speed)
+                .max(0.0)
+                .min(MAX_DELAY_MS),
+            (None, Some(_)) => 0.0,
+            (_, None) => {
+                self.pause();
+                return;
+            }
+        };
+        self._play_task = Some(TimeoutService::new().spawn(
+            Duration::from_millis(delay as u64),
+            self.link.callback(|_| Msg::Tick),
+        ));
+    }
+
+    fn render_replay(&self) -> Html {
+        let recording = match &self.recording {
+            Some(recording) => recording,
+            None => {
+                return html! {
+                    <p class="has-text-grey">
+                        { "Recordings can be downloaded from the game page." }
+                    </p>
+                }
+            }
+        };
+        let player_id = &recording.header.player_id;
+
+        html! {
+            <div class="game-replay">
+                { self.render_controls() }
+                <div class="columns">
+                    <div class="column">
+                        <Table
+                            state=self.game.clone()
+                            player_id=player_id
+                            back_theme=self.settings.back_theme.clone()
+                            animations=self.settings.animations.clone()
+                            key_bindings=self.settings.key_bindings.clone()
+                            />
+                    </div>
+                    <div class="column is-one-quarter">
+                        <Roster state=self.game.clone() player_id=player_id />
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_controls(&self) -> Html {
+        let len = self.len();
+        let play_pause = if self.playing {
+            html! {
+                <button class="button" onclick=self.link.callback(|_| Msg::Pause)>
+                    { "Pause" }
+                </button>
+            }
+        } else {
+            html! {
+                <button class="button is-primary" disabled=(len == 0) onclick=self.link.callback(|_| Msg::Play)>
+                    { "Play" }
+                </button>
+            }
+        };
+
+        let on_seek = self
+            .link
+            .callback(|event: InputData| match event.value.parse() {
+                Ok(position) => Msg::Seek(position),
+                Err(_) => Msg::Ignore,
+            });
+        let on_speed_change = self.link.callback(|event: ChangeData| match event {
+            ChangeData::Select(select) => {
+                let index = select.selected_index().max(0) as usize;
+                Msg::SetSpeed(SPEEDS.get(index).copied().unwrap_or(1.0))
+            }
+            _ => Msg::Ignore,
+        });
+
+        html! {
+            <div class="game-replay-controls">
+                <div class="level">
+                    <div class="level-left">
+                        <div class="buttons has-addons">
+                            <button
+                                class="button"
+                                disabled=(self.position == 0)
+                                aria-label="Step back"
+                                onclick=self.link.callback(|_| Msg::StepBack)
+                                >
+                                { "◀" }
+                            </button>
+                            { play_pause }
+                            <button
+                                class="button"
+                                disabled=(self.position >= len)
+                                aria-label="Step forward"
+                                onclick=self.link.callback(|_| Msg::StepForward)
+                                >
+                                { "▶" }
+                            </button>
+                        </div>
+                    </div>
+                    <div class="level-right">
+                        <div class="field">
+                            <label class="label is-small">{ "Speed" }</label>
+                            <div class="control">
+                                <div class="select is-small">
+                                    <select onchange=on_speed_change>
+                                        {
+                                            for SPEEDS.iter().map(|speed| html! {
+                                                <option selected=*speed == self.speed>
+                                                    { format!("{}×", speed) }
+                                                </option>
+                                            })
+                                        }
+                                    </select>
+                                </div>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+                <input
+                    class="game-replay-scrubber"
+                    type="range"
+                    min="0"
+                    max=len.to_string()
+                    value=self.position.to_string()
+                    aria-label="Position in the recording"
+                    oninput=on_seek
+                    />
+                { self.render_position() }
+            </div>
+        }
+    }
+
+    /// Tells which message was replayed last.
+    fn render_position(&self) -> Html {
+        let recording = match &self.recording {
+            Some(recording) => recording,
+            None => return html! {},
+        };
+        let last = match self.position.checked_sub(1) {
+            Some(index) => &recording.messages[index],
+            None => {
+                return html! {
+                    <p class="help">{ format!("0 / {}", recording.messages.len()) }</p>
+                }
+            }
+        };
+        let elapsed = recording
+            .messages
+            .first()
+            .map_or(0.0, |first| last.timestamp - first.timestamp);
+        let arrow = match last.direction {
+            Direction::Sent => "Sent",
+            Direction::Received => "Received",
+        };
+
+        html! {
+            <p class="help">
+                { format!(
+                    "{} / {} · {:.1} s · {} {}",
+                    self.position,
+                    recording.messages.len(),
+                    elapsed / 1000.0,
+                    arrow,
+                    last.message.type_tag(),
+                ) }
+            </p>
+        }
+    }
+}
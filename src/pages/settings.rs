@@ -0,0 +1,352 @@
+use yew::prelude::*;
+use yewtil::NeqAssign;
+
+use crate::agents::settings_bus::SettingsBus;
+use crate::components::animations::{AnimationSpeed, Animations};
+use crate::components::card_faces::BackTheme;
+use crate::components::keyboard::{key_name, KeyAction, KeyBindings};
+#[cfg(feature = "debug-console")]
+use crate::settings::DebugConsoleSettings;
+use crate::settings::Settings;
+
+pub struct SettingsPage {
+    link: ComponentLink<Self>,
+
+    settings: Settings,
+    settings_bus: Box<dyn Bridge<SettingsBus>>,
+    /// The action waiting for a key to be pressed, to bind it.
+    listening: Option<KeyAction>,
+}
+
+pub enum Msg {
+    SetBackTheme(BackTheme),
+    SetAnimations(Animations),
+    Listen(KeyAction),
+    KeyPressed(KeyboardEvent),
+    Unbind(KeyAction, String),
+    ResetKeyBindings,
+    #[cfg(feature = "debug-console")]
+    SetDebugConsole(DebugConsoleSettings),
+    SettingsChanged(Settings),
+    Ignore,
+}
+
+impl Component for SettingsPage {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let settings_bus = SettingsBus::bridge(link.callback(Msg::SettingsChanged));
+
+        SettingsPage {
+            link,
+
+            settings: Settings::load(),
+            settings_bus,
+            listening: None,
+        }
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::SetBackTheme(back_theme) => self.settings.back_theme = back_theme,
+            Msg::SetAnimations(animations) => self.settings.animations = animations,
+            Msg::Listen(action) => {
+                self.listening = Some(action);
+                return true;
+            }
+            Msg::KeyPressed(event) => {
+                let action = match self.listening.take() {
+                    Some(action) => action,
+                    None => return false,
+                };
+                event.prevent_default();
+                // Escape cancels, so that it can't be bound by mistake.
+                if event.key() != "Escape" || action == KeyAction::Cancel {
+                    self.settings.key_bindings.bind(action, &event.key());
+                }
+            }
+            Msg::Unbind(action, key) => self.settings.key_bindings.unbind(action, &key),
+            Msg::ResetKeyBindings => self.settings.key_bindings = KeyBindings::default(),
+            #[cfg(feature = "debug-console")]
+            Msg::SetDebugConsole(debug_console) => self.settings.debug_console = debug_console,
+            Msg::SettingsChanged(settings) => return self.settings.neq_assign(settings),
+            Msg::Ignore => return false,
+        }
+        self.settings.save();
+        self.settings_bus.send(self.settings.clone());
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <>
+                <h2 class="title is-4">{ "Table" }</h2>
+                { self.render_back_theme_picker() }
+                { self.render_animation_settings() }
+
+                <h2 class="title is-4">{ "Keyboard" }</h2>
+                { self.render_key_bindings() }
+
+                { self.render_debug_console_settings() }
+            </>
+        }
+    }
+}
+
+impl SettingsPage {
+    fn render_back_theme_picker(&self) -> Html {
+        let presets = BackTheme::presets();
+        let onchange = {
+            let presets = presets.clone();
+            self.link.callback(move |event: ChangeData| match event {
+                ChangeData::Select(select) => {
+                    let index = select.selected_index().max(0) as usize;
+                    let (_, theme) = presets.get(index).cloned().unwrap_or_default();
+                    Msg::SetBackTheme(theme)
+                }
+                _ => Msg::Ignore,
+            })
+        };
+
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{ "Card backs" }</label>
+                </div>
+                <div class="field-body">
+                    <div class="select">
+                        <select onchange=onchange>
+                            {
+                                for presets.into_iter().map(|(name, theme)| html! {
+                                    <option selected=theme == self.settings.back_theme>
+                                        { name }
+                                    </option>
+                                })
+                            }
+                        </select>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn render_animation_settings(&self) -> Html {
+        let animations = &self.settings.animations;
+        let on_speed_change = {
+            let animations = animations.clone();
+            self.link.callback(move |event: ChangeData| match event {
+                ChangeData::Select(select) => {
+                    let index = select.selected_index().max(0) as usize;
+                    let speed = AnimationSpeed::ALL
+                        .get(index)
+                        .copied()
+                        .unwrap_or(AnimationSpeed::Normal);
+                    Msg::SetAnimations(Animations {
+                        speed,
+                        ..animations.clone()
+                    })
+                }
+                _ => Msg::Ignore,
+            })
+        };
+        let on_reduced_motion_toggle = {
+            let animations = animations.clone();
+            self.link.callback(move |_| {
+                Msg::SetAnimations(Animations {
+                    reduced_motion: !animations.reduced_motion,
+                    ..animations.clone()
+                })
+            })
+        };
+
+        html! {
+            <div class="field is-horizontal">
+                <div class="field-label is-normal">
+                    <label class="label">{ "Animations" }</label>
+                </div>
+                <div class="field-body">
+                    <div class="field is-grouped">
+                        <div class="control">
+                            <div class="select">
+                                <select
+                                    onchange=on_speed_change
+                                    disabled=animations.reduced_motion
+                                    >
+                                    {
+                                        for AnimationSpeed::ALL.iter().map(|speed| html! {
+                                            <option selected=*speed == animations.speed>
+                                                { speed.name() }
+                                            </option>
+                                        })
+                                    }
+                                </select>
+                            </div>
+                        </div>
+                        <div class="control">
+                            <label class="checkbox">
+                                <input
+                                    type="checkbox"
+                                    checked=animations.reduced_motion
+                                    onclick=on_reduced_motion_toggle
+                                    />
+                                { " Reduced motion" }
+                            </label>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    #[cfg(not(feature = "debug-console"))]
+    fn render_debug_console_settings(&self) -> Html {
+        html! {}
+    }
+
+    #[cfg(feature = "debug-console")]
+    fn render_debug_console_settings(&self) -> Html {
+        use crate::settings::DebugConsoleDock;
+
+        let debug_console = &self.settings.debug_console;
+        let on_visible_toggle = {
+            let debug_console = debug_console.clone();
+            self.link.callback(move |_| {
+                Msg::SetDebugConsole(DebugConsoleSettings {
+                    visible: !debug_console.visible,
+                    ..debug_console.clone()
+                })
+            })
+        };
+        let on_dock_change = {
+            let debug_console = debug_console.clone();
+            self.link.callback(move |event: ChangeData| match event {
+                ChangeData::Select(select) => Msg::SetDebugConsole(DebugConsoleSettings {
+                    dock: if select.selected_index() == 1 {
+                        DebugConsoleDock::Right
+                    } else {
+                        DebugConsoleDock::Bottom
+                    },
+                    ..debug_console.clone()
+                }),
+                _ => Msg::Ignore,
+            })
+        };
+
+        html! {
+            <>
+                <h2 class="title is-4">{ "Developers" }</h2>
+                <div class="field is-horizontal">
+                    <div class="field-label is-normal">
+                        <label class="label">{ "Debug console" }</label>
+                    </div>
+                    <div class="field-body">
+                        <div class="field is-grouped">
+                            <div class="control">
+                                <label class="checkbox">
+                                    <input
+                                        type="checkbox"
+                                        checked=debug_console.visible
+                                        onclick=on_visible_toggle
+                                        />
+                                    { " Show the WebSocket debug console" }
+                                </label>
+                            </div>
+                            <div class="control">
+                                <div class="select">
+                                    <select onchange=on_dock_change aria-label="Dock the console">
+                                        <option selected=debug_console.dock == DebugConsoleDock::Bottom>
+                                            { "Docked at the bottom" }
+                                        </option>
+                                        <option selected=debug_console.dock == DebugConsoleDock::Right>
+                                            { "Docked on the right" }
+                                        </option>
+                                    </select>
+                                </div>
+                            </div>
+                        </div>
+                        <p class="help">
+                            { "Also shown and hidden with " }
+                            <span class="tag">{ crate::components::debug_drawer::SHORTCUT }</span>
+                        </p>
+                    </div>
+                </div>
+            </>
+        }
+    }
+
+    fn render_key_bindings(&self) -> Html {
+        html! {
+            <>
+                <table class="table is-fullwidth">
+                    <tbody>
+                        { for KeyAction::ALL.iter().map(|action| self.render_key_binding(*action)) }
+                        <tr>
+                            <td>{ "Pick one of the options listed in the help" }</td>
+                            <td><span class="tag">{ "1" }</span>{ " … " }<span class="tag">{ "9" }</span></td>
+                            <td></td>
+                        </tr>
+                    </tbody>
+                </table>
+                <button
+                    class="button is-light"
+                    onclick=self.link.callback(|_| Msg::ResetKeyBindings)
+                    >
+                    { "Reset to defaults" }
+                </button>
+            </>
+        }
+    }
+
+    fn render_key_binding(&self, action: KeyAction) -> Html {
+        let render_key = |key: &String| {
+            let onclick = {
+                let key = key.clone();
+                self.link
+                    .callback(move |_| Msg::Unbind(action, key.clone()))
+            };
+            html! {
+                <span class="tag is-medium">
+                    { key_name(key) }
+                    <button class="delete is-small" aria-label="Unbind" onclick=onclick></button>
+                </span>
+            }
+        };
+
+        let add_button = if self.listening == Some(action) {
+            html! {
+                <button
+                    class="button is-small is-warning"
+                    onkeydown=self.link.callback(Msg::KeyPressed)
+                    >
+                    { "Press a key…" }
+                </button>
+            }
+        } else {
+            html! {
+                <button
+                    class="button is-small"
+                    onclick=self.link.callback(move |_| Msg::Listen(action))
+                    >
+                    { "Add key" }
+                </button>
+            }
+        };
+
+        html! {
+            <tr>
+                <td>{ action.description() }</td>
+                <td>
+                    <div class="tags">
+                        { for self.settings.key_bindings.keys(action).iter().map(render_key) }
+                    </div>
+                </td>
+                <td>{ add_button }</td>
+            </tr>
+        }
+    }
+}
@@ -2,6 +2,10 @@ use derive_more::Display;
 use yew::prelude::*;
 use yew_router::prelude::*;
 
+use crate::services::api;
+
+/// The routes of the application. The game IDs and the names they hold are
+/// percent-encoded, see the constructors.
 #[derive(Switch, Debug, Clone, Display, PartialEq)]
 pub enum AppRoute {
     #[to = "/!"]
@@ -24,11 +28,46 @@ pub enum AppRoute {
     #[display(fmt = "/game/play/{}?as={}", game_id, player_id)]
     PlayGame { game_id: String, player_id: String },
 
+    #[to = "/game/watch/{game_id}?as={username}"]
+    #[display(fmt = "/game/watch/{}?as={}", game_id, username)]
+    WatchGame { game_id: String, username: String },
+
+    #[to = "/game/replay"]
+    #[display(fmt = "/game/replay")]
+    Replay,
+
+    #[to = "/settings"]
+    #[display(fmt = "/settings")]
+    Settings,
+
     #[to = "/not_found{*}"]
     #[display(fmt = "/not_found{}", _0)]
     NotFound(String),
 }
 
+impl AppRoute {
+    pub fn join_game(game_id: &str, username: &str) -> Self {
+        AppRoute::JoinGame {
+            game_id: api::encode(game_id),
+            username: api::encode(username),
+        }
+    }
+
+    pub fn play_game(game_id: &str, player_id: &str) -> Self {
+        AppRoute::PlayGame {
+            game_id: api::encode(game_id),
+            player_id: api::encode(player_id),
+        }
+    }
+
+    pub fn watch_game(game_id: &str, username: &str) -> Self {
+        AppRoute::WatchGame {
+            game_id: api::encode(game_id),
+            username: api::encode(username),
+        }
+    }
+}
+
 #[allow(unused)]
 pub type NavBtn = RouterButton<AppRoute>;
 
@@ -78,6 +117,11 @@ impl Breadcrumb for AppRoute {
             AppRoute::PlayGame { .. } => {
                 vec![("Games", AppRoute::ListGames), ("Play game", self.clone())]
             }
+            AppRoute::WatchGame { .. } => {
+                vec![("Games", AppRoute::ListGames), ("Watch game", self.clone())]
+            }
+            AppRoute::Replay => vec![("Games", AppRoute::ListGames), ("Replay", self.clone())],
+            AppRoute::Settings => vec![("Settings", self.clone())],
             AppRoute::NotFound(_) => vec![("Not found", self.clone())],
         }
     }
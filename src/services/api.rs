@@ -0,0 +1,201 @@
+//! URLs of the API of the game server, and validation of the values put in
+//! them.
+//!
+//! The URLs are only built through the typed constructors of [`ApiUrl`], which
+//! percent-encode the path segments and the query parameters, so that any name
+//! can be sent to the server.
+
+use std::fmt;
+
+use crate::agents::game_ws_mgr::GameWsConnectionInfo;
+use crate::wire;
+
+const MAX_USERNAME_LENGTH: usize = 32;
+const MAX_GAME_ID_LENGTH: usize = 64;
+
+/// A URL of the API, relative to the site.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiUrl {
+    path: String,
+    query: String,
+}
+
+impl ApiUrl {
+    fn new() -> Self {
+        ApiUrl {
+            path: "/api".to_string(),
+            query: String::new(),
+        }
+    }
+
+    fn segment(mut self, segment: &str) -> Self {
+        self.path.push('/');
+        self.path.push_str(&encode(segment));
+        self
+    }
+
+    fn param(mut self, key: &str, value: &str) -> Self {
+        self.query
+            .push(if self.query.is_empty() { '?' } else { '&' });
+        self.query.push_str(&encode(key));
+        self.query.push('=');
+        self.query.push_str(&encode(value));
+        self
+    }
+
+    pub fn round(game_id: &str) -> Self {
+        ApiUrl::new().segment("round").segment(game_id)
+    }
+
+    pub fn join_round(game_id: &str, username: &str) -> Self {
+        ApiUrl::round(game_id)
+            .segment("join")
+            .param("username", username)
+    }
+
+    /// The URL of the WebSocket of a round.
+    pub fn round_socket(info: &GameWsConnectionInfo, encoding: wire::Encoding) -> Self {
+        let url = ApiUrl::round(&info.game_id).segment("join");
+        match &info.spectator {
+            Some(name) => url
+                .param("playerId", name)
+                .param("encoding", encoding.query_value())
+                .param("spectator", "true"),
+            None => url
+                .param("playerId", &info.player_id)
+                .param("encoding", encoding.query_value()),
+        }
+    }
+}
+
+impl fmt::Display for ApiUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.path, self.query)
+    }
+}
+
+/// Percent-encodes everything but the unreserved characters of RFC 3986.
+pub fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(char::from(byte))
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Decodes a percent-encoded value, keeping the invalid escapes as they are.
+pub fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match (bytes[i], bytes.get(i + 1..i + 3)) {
+            (b'%', Some(hex)) => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+pub fn validate_username(username: &str) -> Result<(), String> {
+    if username.trim().is_empty() {
+        Err("Enter a player name.".to_string())
+    } else if username.trim() != username {
+        Err("The player name can't start or end with spaces.".to_string())
+    } else if username.chars().count() > MAX_USERNAME_LENGTH {
+        Err(format!(
+            "The player name is too long, {} characters at most.",
+            MAX_USERNAME_LENGTH
+        ))
+    } else if username.chars().any(char::is_control) {
+        Err("The player name can't contain control characters.".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn validate_game_id(game_id: &str) -> Result<(), String> {
+    if game_id.trim().is_empty() {
+        Err("Enter the ID of the game.".to_string())
+    } else if game_id.len() > MAX_GAME_ID_LENGTH {
+        Err(format!(
+            "The game ID is too long, {} characters at most.",
+            MAX_GAME_ID_LENGTH
+        ))
+    } else if !game_id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Err("The game ID can only contain letters, digits, '-' and '_'.".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_urls() {
+        assert_eq!(
+            ApiUrl::join_round("abc-123", "Tom & Jerry #1").to_string(),
+            "/api/round/abc-123/join?username=Tom%20%26%20Jerry%20%231"
+        );
+        assert_eq!(ApiUrl::round("a/b?c").to_string(), "/api/round/a%2Fb%3Fc");
+
+        let info = GameWsConnectionInfo {
+            game_id: "g1".to_string(),
+            player_id: String::new(),
+            spectator: Some("Zoé".to_string()),
+        };
+        assert_eq!(
+            ApiUrl::round_socket(&info, wire::Encoding::Json).to_string(),
+            "/api/round/g1/join?playerId=Zo%C3%A9&encoding=json&spectator=true"
+        );
+        assert_eq!(info.identity(), "spectator Zoé");
+    }
+
+    #[test]
+    fn round_trips_through_encoding() {
+        for value in &["Tom & Jerry #1", "Zoé", "100%", "a+b=c", "~_.-"] {
+            assert_eq!(decode(&encode(value)), *value);
+        }
+        assert_eq!(encode("~_.-"), "~_.-");
+        assert_eq!(decode("100%"), "100%");
+        assert_eq!(decode("%zz%4"), "%zz%4");
+    }
+
+    #[test]
+    fn validates_names() {
+        assert_eq!(validate_username("Zoé & co"), Ok(()));
+        assert!(validate_username("").is_err());
+        assert!(validate_username(" Zoé").is_err());
+        assert!(validate_username("a\u{7}").is_err());
+        assert!(validate_username(&"x".repeat(33)).is_err());
+
+        assert_eq!(validate_game_id("abc-123_X"), Ok(()));
+        assert_eq!(
+            validate_game_id("abc 123"),
+            Err("The game ID can only contain letters, digits, '-' and '_'.".to_string())
+        );
+        assert!(validate_game_id("  ").is_err());
+    }
+}
@@ -0,0 +1,27 @@
+//! Files downloaded from the browser.
+
+use anyhow::{anyhow, Result};
+use wasm_bindgen::JsCast;
+
+/// Has the browser save a text file with the given name.
+pub fn download(filename: &str, mime_type: &str, content: &str) -> Result<()> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| anyhow!("No document to download {} from", filename))?;
+    let link = document
+        .create_element("a")
+        .map_err(|e| anyhow!("{:?}", e))?
+        .dyn_into::<web_sys::HtmlElement>()
+        .map_err(|e| anyhow!("{:?}", e))?;
+
+    let url = format!(
+        "data:{};charset=utf-8,{}",
+        mime_type,
+        js_sys::encode_uri_component(content)
+    );
+    link.set_attribute("href", &url)
+        .and_then(|_| link.set_attribute("download", filename))
+        .map_err(|e| anyhow!("{:?}", e))?;
+    link.click();
+    Ok(())
+}
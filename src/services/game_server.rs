@@ -4,6 +4,8 @@ use yew::callback::Callback;
 use yew::format::{Json, Nothing};
 use yew::services::fetch::{FetchService, Request, Response};
 
+use super::api::ApiUrl;
+
 // Re-exported for convenience.
 pub use yew::services::fetch::FetchTask;
 
@@ -37,6 +39,22 @@ pub struct JoinRoundResponse {
     pub players: Vec<String>,
 }
 
+/// A round, as seen by anyone.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RoundResponse {
+    pub id: String,
+    pub game_id: String,
+    pub status: String,
+    pub created_on: String,
+    /// Player ID of the creator of the round, who can remove players.
+    pub created_by: String,
+    pub min_players: u32,
+    pub max_players: u32,
+    pub public: bool,
+    pub players: Vec<String>,
+}
+
 #[derive(Default)]
 pub struct GameServerService {}
 
@@ -77,11 +95,7 @@ impl GameServerService {
         username: impl AsRef<str>,
         callback: Callback<Result<JoinRoundResponse>>,
     ) -> FetchTask {
-        let url = format!(
-            "/api/round/{}/join?username={}",
-            game_id.as_ref(),
-            username.as_ref()
-        );
+        let url = ApiUrl::join_round(game_id.as_ref(), username.as_ref()).to_string();
         let request = Request::get(url.as_str()).body(Nothing).unwrap();
 
         let handler = move |response: Response<Json<Result<JoinRoundResponse>>>| {
@@ -94,4 +108,23 @@ impl GameServerService {
         };
         FetchService::fetch(request, handler.into()).unwrap()
     }
+
+    pub fn get_round(
+        &mut self,
+        game_id: impl AsRef<str>,
+        callback: Callback<Result<RoundResponse>>,
+    ) -> FetchTask {
+        let url = ApiUrl::round(game_id.as_ref()).to_string();
+        let request = Request::get(url.as_str()).body(Nothing).unwrap();
+
+        let handler = move |response: Response<Json<Result<RoundResponse>>>| {
+            let (meta, Json(data)) = response.into_parts();
+            if meta.status.is_success() {
+                callback.emit(data)
+            } else {
+                callback.emit(Err(anyhow!("{}", meta.status)))
+            }
+        };
+        FetchService::fetch(request, handler.into()).unwrap()
+    }
 }
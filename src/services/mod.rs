@@ -1 +1,4 @@
+pub mod api;
+pub mod files;
 pub mod game_server;
+pub mod transport;
@@ -0,0 +1,269 @@
+use anyhow::{anyhow, Result};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use yew::callback::Callback;
+
+use super::{Connection, Frame, Transport, TransportStatus};
+use crate::wire;
+
+/// In-memory transport, where the server is played by the code holding the
+/// `Loopback`. Clones share the same state, so that one clone can be given to
+/// `GameWsMgr` while another one scripts the server messages and inspects what
+/// the client sent.
+///
+/// Only one connection is alive at a time: connecting again replaces it.
+#[derive(Clone, Default)]
+pub struct Loopback {
+    state: Rc<RefCell<LoopbackState>>,
+}
+
+#[derive(Default)]
+struct LoopbackState {
+    manual_open: bool,
+    connection_id: usize,
+    url: Option<String>,
+    on_frame: Option<Callback<Result<Frame>>>,
+    on_status: Option<Callback<TransportStatus>>,
+    sent: Vec<Frame>,
+}
+
+#[derive(Debug)]
+struct LoopbackConnection {
+    id: usize,
+    state: Rc<RefCell<LoopbackState>>,
+}
+
+impl Loopback {
+    /// Creates a loopback whose connections are opened as soon as requested.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a loopback whose connections stay pending until `open` is
+    /// called.
+    pub fn with_manual_open() -> Self {
+        let loopback = Self::default();
+        loopback.state.borrow_mut().manual_open = true;
+        loopback
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state.borrow().url.is_some()
+    }
+
+    /// The URL of the current connection, if any.
+    pub fn url(&self) -> Option<String> {
+        self.state.borrow().url.clone()
+    }
+
+    /// Accepts the pending connection.
+    pub fn open(&self) {
+        self.notify(TransportStatus::Opened);
+    }
+
+    /// Closes the connection from the server side.
+    pub fn close(&self) {
+        self.notify(TransportStatus::Closed);
+        self.state.borrow_mut().disconnect();
+    }
+
+    /// Makes the connection fail.
+    pub fn fail(&self) {
+        self.notify(TransportStatus::Error);
+        self.state.borrow_mut().disconnect();
+    }
+
+    /// Sends a message to the client, encoded in JSON.
+    pub fn push(&self, message: &wire::Message) -> Result<()> {
+        let text = serde_json::to_string(message)?;
+        self.push_frame(Frame::Text(text))
+    }
+
+    /// Sends a raw frame to the client.
+    pub fn push_frame(&self, frame: Frame) -> Result<()> {
+        // The callback is cloned so that it can use the loopback.
+        let on_frame = self.state.borrow().on_frame.clone();
+        let on_frame = on_frame.ok_or_else(|| anyhow!("The loopback is not connected"))?;
+        on_frame.emit(Ok(frame));
+        Ok(())
+    }
+
+    /// Returns the frames sent by the client since the last call.
+    pub fn take_sent_frames(&self) -> Vec<Frame> {
+        std::mem::take(&mut self.state.borrow_mut().sent)
+    }
+
+    /// Returns the messages sent by the client since the last call.
+    pub fn take_sent_messages(&self) -> Vec<Result<wire::Message>> {
+        self.take_sent_frames()
+            .into_iter()
+            .map(|frame| match frame {
+                Frame::Text(text) => wire::Encoding::Json.decode(text.as_bytes()),
+                Frame::Binary(bytes) => wire::Encoding::PREFERRED.decode(&bytes),
+            })
+            .collect()
+    }
+
+    fn notify(&self, status: TransportStatus) {
+        let on_status = self.state.borrow().on_status.clone();
+        if let Some(on_status) = on_status {
+            on_status.emit(status);
+        }
+    }
+}
+
+impl fmt::Debug for Loopback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("Loopback")
+            .field("url", &state.url)
+            .field("sent", &state.sent)
+            .finish()
+    }
+}
+
+impl Transport for Loopback {
+    fn connect(
+        &mut self,
+        url: &str,
+        on_frame: Callback<Result<Frame>>,
+        on_status: Callback<TransportStatus>,
+    ) -> Result<Box<dyn Connection>> {
+        let (id, manual_open) = {
+            let mut state = self.state.borrow_mut();
+            state.connection_id = state.connection_id.wrapping_add(1);
+            state.url = Some(url.to_string());
+            state.on_frame = Some(on_frame);
+            state.on_status = Some(on_status);
+            (state.connection_id, state.manual_open)
+        };
+
+        if !manual_open {
+            self.open();
+        }
+
+        Ok(Box::new(LoopbackConnection {
+            id,
+            state: self.state.clone(),
+        }))
+    }
+}
+
+impl LoopbackState {
+    fn disconnect(&mut self) {
+        self.url = None;
+        self.on_frame = None;
+        self.on_status = None;
+    }
+}
+
+impl fmt::Debug for LoopbackState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoopbackState")
+            .field("connection_id", &self.connection_id)
+            .field("url", &self.url)
+            .finish()
+    }
+}
+
+impl Connection for LoopbackConnection {
+    fn send(&mut self, frame: Frame) {
+        let mut state = self.state.borrow_mut();
+        if state.connection_id == self.id && state.url.is_some() {
+            state.sent.push(frame);
+        } else {
+            log::warn!("Sent on a closed loopback connection, ignoring.");
+        }
+    }
+}
+
+impl Drop for LoopbackConnection {
+    fn drop(&mut self) {
+        let mut state = self.state.borrow_mut();
+        if state.connection_id == self.id {
+            state.disconnect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connect(loopback: &mut Loopback) -> (Box<dyn Connection>, Rc<RefCell<Vec<String>>>) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let frame_events = events.clone();
+        let status_events = events.clone();
+        let connection = loopback
+            .connect(
+                "ws://loopback/",
+                Callback::from(move |frame: Result<Frame>| {
+                    frame_events
+                        .borrow_mut()
+                        .push(format!("{:?}", frame.unwrap()))
+                }),
+                Callback::from(move |status: TransportStatus| {
+                    status_events.borrow_mut().push(format!("{:?}", status))
+                }),
+            )
+            .expect("failed to connect");
+        (connection, events)
+    }
+
+    #[test]
+    fn opens_and_receives() {
+        let mut loopback = Loopback::new();
+        let (_connection, events) = connect(&mut loopback);
+        assert_eq!(loopback.url().as_deref(), Some("ws://loopback/"));
+
+        loopback.push(&wire::Message::Ping).unwrap();
+        loopback.close();
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                "Opened".to_string(),
+                r#"Text("{\"type\":\"PING\"}")"#.to_string(),
+                "Closed".to_string(),
+            ]
+        );
+        assert!(!loopback.is_connected());
+        assert!(loopback.push(&wire::Message::Ping).is_err());
+    }
+
+    #[test]
+    fn manual_open() {
+        let mut loopback = Loopback::with_manual_open();
+        let (_connection, events) = connect(&mut loopback);
+        assert!(events.borrow().is_empty());
+
+        loopback.open();
+        assert_eq!(*events.borrow(), vec!["Opened".to_string()]);
+    }
+
+    #[test]
+    fn records_sent_messages() {
+        let mut loopback = Loopback::new();
+        let (mut connection, _events) = connect(&mut loopback);
+
+        connection.send(Frame::Text(r#"{"type":"PONG"}"#.to_string()));
+        connection.send(Frame::Text("not JSON".to_string()));
+
+        let sent = loopback.take_sent_messages();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].as_ref().unwrap(), &wire::Message::Pong);
+        assert!(sent[1].is_err());
+        assert!(loopback.take_sent_frames().is_empty());
+    }
+
+    #[test]
+    fn dropping_the_connection_disconnects() {
+        let mut loopback = Loopback::new();
+        let (connection, _events) = connect(&mut loopback);
+        assert!(loopback.is_connected());
+
+        drop(connection);
+        assert!(!loopback.is_connected());
+    }
+}
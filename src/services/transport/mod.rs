@@ -0,0 +1,63 @@
+//! Transports carrying the messages exchanged with the game server.
+//!
+//! `GameWsMgr` opens its connections through a [`Transport`], which is a
+//! WebSocket in the application, and can be replaced by a [`Loopback`] to
+//! script the server in tests or to play offline with the debug tools.
+
+// Compiled with the debug console too, for the tools playing offline.
+#[cfg(any(test, feature = "debug-console"))]
+#[cfg_attr(not(test), allow(dead_code))]
+mod loopback;
+mod websocket;
+
+#[cfg(any(test, feature = "debug-console"))]
+#[cfg_attr(not(test), allow(unused_imports))]
+pub use loopback::Loopback;
+pub use websocket::WebSocketTransport;
+
+use anyhow::Result;
+use std::fmt;
+use yew::callback::Callback;
+
+/// A frame exchanged with the game server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+#[cfg(feature = "debug-console")]
+impl Frame {
+    /// The size of the data of the frame, in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            Frame::Text(text) => text.len(),
+            Frame::Binary(bytes) => bytes.len(),
+        }
+    }
+}
+
+/// Status changes of a connection, mirroring the ones of a WebSocket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransportStatus {
+    Opened,
+    Closed,
+    Error,
+}
+
+pub trait Transport: fmt::Debug {
+    /// Opens a connection to the given URL of the API, relative to the site.
+    /// Frames can only be sent once `on_status` has been notified with
+    /// `TransportStatus::Opened`.
+    fn connect(
+        &mut self,
+        url: &str,
+        on_frame: Callback<Result<Frame>>,
+        on_status: Callback<TransportStatus>,
+    ) -> Result<Box<dyn Connection>>;
+}
+
+/// A connection opened by a [`Transport`], which is closed when dropped.
+pub trait Connection: fmt::Debug {
+    fn send(&mut self, frame: Frame);
+}
@@ -0,0 +1,104 @@
+use anyhow::{anyhow, Context as _, Result};
+use log::*;
+use std::fmt;
+use yew::callback::Callback;
+use yew::format::{Binary, Text};
+use yew::services::websocket::{WebSocketService, WebSocketStatus, WebSocketTask};
+
+use super::{Connection, Frame, Transport, TransportStatus};
+
+/// Transport talking to an actual server, using a WebSocket.
+pub struct WebSocketTransport {
+    service: WebSocketService,
+}
+
+impl WebSocketTransport {
+    pub fn new() -> Self {
+        WebSocketTransport {
+            service: WebSocketService::new(),
+        }
+    }
+}
+
+impl Default for WebSocketTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for WebSocketTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WebSocketTransport")
+    }
+}
+
+impl Transport for WebSocketTransport {
+    fn connect(
+        &mut self,
+        url: &str,
+        on_frame: Callback<Result<Frame>>,
+        on_status: Callback<TransportStatus>,
+    ) -> Result<Box<dyn Connection>> {
+        // Build the URL with the right base, i.e. the same as the site
+        let base = web_sys::window().unwrap().location().href().unwrap();
+        let url = web_sys::Url::new_with_base(url, &base)
+            .map_err(|js_err| anyhow!("{:?}", js_err))
+            .context("Failed to build WebSocket address")?;
+        url.set_protocol("ws");
+        let url = url.href();
+
+        debug!("Connecting to WebSocket using URL: {}", &url);
+
+        let callback = on_frame.reform(|IncomingFrame(frame)| frame);
+        let notification = on_status.reform(TransportStatus::from);
+        match self.service.connect(&url, callback, notification) {
+            Ok(task) => Ok(Box::new(WebSocket { task })),
+            Err(e) => Err(anyhow!("Failed to connect WebSocket: {}", e)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct WebSocket {
+    task: WebSocketTask,
+}
+
+impl Connection for WebSocket {
+    fn send(&mut self, frame: Frame) {
+        match frame {
+            Frame::Text(text) => {
+                let text: Text = Ok(text);
+                self.task.send(text);
+            }
+            Frame::Binary(bytes) => {
+                let bytes: Binary = Ok(bytes);
+                self.task.send_binary(bytes);
+            }
+        }
+    }
+}
+
+/// Frame received on the WebSocket, whatever its type.
+struct IncomingFrame(Result<Frame>);
+
+impl From<Text> for IncomingFrame {
+    fn from(text: Text) -> Self {
+        IncomingFrame(text.map(Frame::Text))
+    }
+}
+
+impl From<Binary> for IncomingFrame {
+    fn from(binary: Binary) -> Self {
+        IncomingFrame(binary.map(Frame::Binary))
+    }
+}
+
+impl From<WebSocketStatus> for TransportStatus {
+    fn from(status: WebSocketStatus) -> Self {
+        match status {
+            WebSocketStatus::Opened => TransportStatus::Opened,
+            WebSocketStatus::Closed => TransportStatus::Closed,
+            WebSocketStatus::Error => TransportStatus::Error,
+        }
+    }
+}
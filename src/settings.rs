@@ -0,0 +1,77 @@
+//! Preferences of the player, kept in the local storage of the browser.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use yew::format::Json;
+use yew::services::storage::{Area, StorageService};
+
+use crate::components::animations::Animations;
+use crate::components::card_faces::BackTheme;
+use crate::components::keyboard::KeyBindings;
+
+const KEY: &str = "cards-client-rs.settings";
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Settings {
+    #[serde(default)]
+    pub back_theme: BackTheme,
+    #[serde(default)]
+    pub animations: Animations,
+    #[serde(default)]
+    pub key_bindings: KeyBindings,
+    /// Player IDs of the players whose chat messages are hidden.
+    #[serde(default)]
+    pub muted_players: BTreeSet<String>,
+    /// Only used by the builds with the `debug-console` feature.
+    #[serde(default)]
+    pub debug_console: DebugConsoleSettings,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DebugConsoleSettings {
+    pub visible: bool,
+    pub dock: DebugConsoleDock,
+}
+
+/// The side of the window the debug console is docked to.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugConsoleDock {
+    Bottom,
+    Right,
+}
+
+impl Default for DebugConsoleDock {
+    fn default() -> Self {
+        DebugConsoleDock::Bottom
+    }
+}
+
+impl Settings {
+    /// Restores the saved settings, or returns the default ones.
+    pub fn load() -> Self {
+        let restored = StorageService::new(Area::Local)
+            .ok()
+            .map(|storage| storage.restore(KEY));
+        match restored {
+            Some(Json(Ok(settings))) => {
+                let settings: Settings = settings;
+                Settings {
+                    key_bindings: settings.key_bindings.with_defaults(),
+                    ..settings
+                }
+            }
+            _ => Settings {
+                animations: Animations::from_browser(),
+                ..Settings::default()
+            },
+        }
+    }
+
+    pub fn save(&self) {
+        match StorageService::new(Area::Local) {
+            Ok(mut storage) => storage.store(KEY, Json(self)),
+            Err(error) => log::error!("Failed to save the settings: {}", error),
+        }
+    }
+}
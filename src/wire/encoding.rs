@@ -0,0 +1,70 @@
+//! Encodings of the wire protocol.
+//!
+//! Messages are encoded in JSON by default. When the `msgpack` feature is
+//! enabled, the client asks the server to use MessagePack when joining a round,
+//! and switches to it once the server answers with binary frames. Servers that
+//! don't support it simply ignore the request and keep talking JSON.
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Message;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Json,
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Encoding::Json
+    }
+}
+
+impl Encoding {
+    /// The encoding the client asks for when joining a round.
+    #[cfg(feature = "msgpack")]
+    pub const PREFERRED: Encoding = Encoding::MessagePack;
+    #[cfg(not(feature = "msgpack"))]
+    pub const PREFERRED: Encoding = Encoding::Json;
+
+    /// Value of the `encoding` query parameter sent when joining a round.
+    pub fn query_value(self) -> &'static str {
+        match self {
+            Encoding::Json => "json",
+            #[cfg(feature = "msgpack")]
+            Encoding::MessagePack => "msgpack",
+        }
+    }
+
+    pub fn is_binary(self) -> bool {
+        match self {
+            Encoding::Json => false,
+            #[cfg(feature = "msgpack")]
+            Encoding::MessagePack => true,
+        }
+    }
+
+    pub fn encode(self, message: &Message) -> Result<Vec<u8>> {
+        match self {
+            Encoding::Json => serde_json::to_vec(message).context("Failed to encode JSON"),
+            // Structs must be encoded as maps for the tagged enums to work.
+            #[cfg(feature = "msgpack")]
+            Encoding::MessagePack => {
+                rmp_serde::to_vec_named(message).context("Failed to encode MessagePack")
+            }
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> Result<Message> {
+        match self {
+            Encoding::Json => serde_json::from_slice(bytes).context("Failed to decode JSON"),
+            #[cfg(feature = "msgpack")]
+            Encoding::MessagePack => {
+                rmp_serde::from_read_ref(bytes).context("Failed to decode MessagePack")
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Example values of the messages, used as templates to write messages by
+//! hand in the debug console.
+
+use std::collections::BTreeMap;
+
+use super::*;
+
+impl Message {
+    /// An example of each of the known types of message, in the order of
+    /// `KNOWN_TYPES`.
+    pub fn examples() -> Vec<Message> {
+        let card = Card {
+            name: "Queen of hearts".to_string(),
+            description: String::new(),
+            front_image: None,
+            back_image: None,
+            state: CardState {
+                suit: Some("hearts".to_string()),
+                value: Some("Q".to_string()),
+                properties: BTreeMap::new(),
+            },
+            owner: None,
+            visibility: Visibility::Everyone,
+        };
+
+        vec![
+            Message::Ping,
+            Message::Pong,
+            Close::new(CloseReason::Leaving).into(),
+            PlayerConnected {
+                message: "Player 1 joined the game".to_string(),
+                player_id: "player1".to_string(),
+                username: "Player 1".to_string(),
+            }
+            .into(),
+            PlayerDisconnected {
+                player_id: "player1".to_string(),
+                username: "Player 1".to_string(),
+            }
+            .into(),
+            PlayerReconnected {
+                player_id: "player1".to_string(),
+                username: "Player 1".to_string(),
+            }
+            .into(),
+            PlayerLeft {
+                player_id: "player1".to_string(),
+                username: "Player 1".to_string(),
+                kicked: false,
+            }
+            .into(),
+            Message::LeaveRound,
+            KickPlayer {
+                player_id: "player1".to_string(),
+            }
+            .into(),
+            TurnChanged {
+                player_id: "player1".to_string(),
+                time_limit: Some(30),
+                scores: vec![("player1".to_string(), 0)].into_iter().collect(),
+            }
+            .into(),
+            Spectating {
+                seats: vec!["player1".to_string()],
+            }
+            .into(),
+            WatchSeat {
+                player_id: Some("player1".to_string()),
+            }
+            .into(),
+            Message::StartGame,
+            Message::GameStarted,
+            GameFinished {
+                winners: vec!["player1".to_string()],
+            }
+            .into(),
+            Error {
+                messages: vec!["Something went wrong.".to_string()],
+            }
+            .into(),
+            ActionAwaited {
+                all_of: vec![AwaitedAction::OnClick {
+                    target_component: "card1".to_string(),
+                }],
+            }
+            .into(),
+            InterfaceUpdate {
+                components: vec![InterfaceComponent {
+                    id: "hand1".into(),
+                    position: ComponentPosition::Bottom,
+                }],
+            }
+            .into(),
+            ComponentsUpdates {
+                updates: vec![
+                    ComponentUpdate::Create {
+                        id: "card1".into(),
+                        component: card.into(),
+                    },
+                    ComponentUpdate::Create {
+                        id: "hand1".into(),
+                        component: Hand {
+                            cards: vec!["card1".into()],
+                            owner: Some("player1".to_string()),
+                            visibility: Visibility::Owner,
+                        }
+                        .into(),
+                    },
+                ],
+            }
+            .into(),
+            PerformAction::from(AwaitedAction::OnClick {
+                target_component: "card1".to_string(),
+            })
+            .into(),
+            SendChatMessage {
+                text: "Hello!".to_string(),
+                target: None,
+            }
+            .into(),
+            ChatMessage {
+                sender: "player1".to_string(),
+                text: "Hello!".to_string(),
+                timestamp: 0,
+                target: None,
+            }
+            .into(),
+        ]
+    }
+
+    /// Decodes a message written by hand, telling what is wrong with it in
+    /// terms of the protocol rather than only of JSON. Messages of an unknown
+    /// type are rejected, since the server wouldn't understand them either.
+    pub fn parse_strict(text: &str) -> Result<Message, String> {
+        let value =
+            serde_json::from_str::<Value>(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+        let r#type = value
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        match serde_json::from_value::<Message>(value) {
+            Ok(Message::Unknown { r#type, .. }) => Err(format!(
+                "Unknown type {:?}, expected one of: {}",
+                r#type,
+                Message::KNOWN_TYPES.join(", ")
+            )),
+            Ok(message) => Ok(message),
+            Err(e) if r#type.is_empty() => Err(format!("Invalid message: {}", e)),
+            Err(e) => Err(format!("Invalid {} message: {}", r#type, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_all_the_known_types() {
+        let examples = Message::examples();
+        let types = examples.iter().map(Message::type_tag).collect::<Vec<_>>();
+        assert_eq!(types, Message::KNOWN_TYPES);
+
+        for example in examples {
+            let encoded = serde_json::to_string_pretty(&example).unwrap();
+            assert_eq!(Message::parse_strict(&encoded), Ok(example));
+        }
+    }
+
+    #[test]
+    fn reports_field_errors() {
+        assert_eq!(
+            Message::parse_strict(r#"{"type":"PLAYER_DISCONNECTED"}"#),
+            Err("Invalid PLAYER_DISCONNECTED message: missing field `username`".to_string())
+        );
+        assert_eq!(
+            Message::parse_strict(r#"{"type":"ACTION_AWAITED","all_of":{}}"#),
+            Err(
+                "Invalid ACTION_AWAITED message: invalid type: map, expected a sequence"
+                    .to_string()
+            )
+        );
+        assert!(Message::parse_strict(r#"{"type":"START"}"#)
+            .unwrap_err()
+            .starts_with("Unknown type \"START\", expected one of: PING, PONG"));
+        assert!(Message::parse_strict("{")
+            .unwrap_err()
+            .starts_with("Invalid JSON: "));
+    }
+}
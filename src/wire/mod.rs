@@ -1,8 +1,18 @@
 use derive_more::From;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[macro_use]
+mod tolerant;
+mod encoding;
+#[cfg(any(test, feature = "debug-console"))]
+mod examples;
+
+pub use encoding::Encoding;
 
 #[derive(Serialize, Deserialize, From, Clone, Debug, PartialEq)]
-#[serde(tag = "type")]
+#[serde(tag = "type", remote = "Self")]
 pub enum Message {
     #[serde(rename = "PING")]
     Ping,
@@ -11,12 +21,44 @@ pub enum Message {
     Pong,
 
     #[serde(rename = "CLOSE")]
-    Close,
+    #[from]
+    Close(Close),
 
     #[serde(rename = "PLAYER_CONNECTED")]
     #[from]
     PlayerConnected(PlayerConnected),
 
+    #[serde(rename = "PLAYER_DISCONNECTED")]
+    #[from]
+    PlayerDisconnected(PlayerDisconnected),
+
+    #[serde(rename = "PLAYER_RECONNECTED")]
+    #[from]
+    PlayerReconnected(PlayerReconnected),
+
+    #[serde(rename = "PLAYER_LEFT")]
+    #[from]
+    PlayerLeft(PlayerLeft),
+
+    #[serde(rename = "LEAVE_ROUND")]
+    LeaveRound,
+
+    #[serde(rename = "KICK_PLAYER")]
+    #[from]
+    KickPlayer(KickPlayer),
+
+    #[serde(rename = "TURN_CHANGED")]
+    #[from]
+    TurnChanged(TurnChanged),
+
+    #[serde(rename = "SPECTATING")]
+    #[from]
+    Spectating(Spectating),
+
+    #[serde(rename = "WATCH_SEAT")]
+    #[from]
+    WatchSeat(WatchSeat),
+
     #[serde(rename = "START_GAME")]
     StartGame,
 
@@ -42,57 +84,339 @@ pub enum Message {
     #[serde(rename = "COMPONENTS_UPDATES")]
     #[from]
     ComponentsUpdates(ComponentsUpdates),
+
+    #[serde(rename = "PERFORM_ACTION")]
+    #[from]
+    PerformAction(PerformAction),
+
+    #[serde(rename = "SEND_CHAT_MESSAGE")]
+    #[from]
+    SendChatMessage(SendChatMessage),
+
+    #[serde(rename = "CHAT_MESSAGE")]
+    #[from]
+    ChatMessage(ChatMessage),
+
+    #[serde(skip)]
+    Unknown { r#type: String, raw: Value },
+}
+
+tolerant_tagged_enum!(Message {
+    Ping => "PING",
+    Pong => "PONG",
+    Close => "CLOSE",
+    PlayerConnected => "PLAYER_CONNECTED",
+    PlayerDisconnected => "PLAYER_DISCONNECTED",
+    PlayerReconnected => "PLAYER_RECONNECTED",
+    PlayerLeft => "PLAYER_LEFT",
+    LeaveRound => "LEAVE_ROUND",
+    KickPlayer => "KICK_PLAYER",
+    TurnChanged => "TURN_CHANGED",
+    Spectating => "SPECTATING",
+    WatchSeat => "WATCH_SEAT",
+    StartGame => "START_GAME",
+    GameStarted => "GAME_STARTED",
+    GameFinished => "GAME_FINISHED",
+    Error => "ERROR",
+    ActionAwaited => "ACTION_AWAITED",
+    InterfaceUpdate => "INTERFACE_UPDATE",
+    ComponentsUpdates => "COMPONENTS_UPDATES",
+    PerformAction => "PERFORM_ACTION",
+    SendChatMessage => "SEND_CHAT_MESSAGE",
+    ChatMessage => "CHAT_MESSAGE",
+});
+
+/// Sent by either side before closing the connection on purpose, to tell the
+/// other side not to reconnect unless the reason allows it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Close {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<CloseReason>,
+    /// Explains the reason to the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CloseReason {
+    /// The player left the game.
+    Leaving,
+    /// The player was removed from the game by its creator.
+    Kicked,
+    RoundEnded,
+    /// The server is going away for a while, and can be joined again later.
+    ServerRestarting,
+    /// A reason unknown to this version of the client.
+    #[serde(other)]
+    Other,
+}
+
+impl Close {
+    pub fn new(reason: CloseReason) -> Self {
+        Close {
+            reason: Some(reason),
+            message: None,
+        }
+    }
+
+    /// The explanation shown to the user, the one of the server if any.
+    pub fn user_message(&self) -> String {
+        if let Some(message) = &self.message {
+            return message.clone();
+        }
+        match self.reason {
+            Some(CloseReason::Leaving) => "You left the game.",
+            Some(CloseReason::Kicked) => "You were removed from the game.",
+            Some(CloseReason::RoundEnded) => "The round is over.",
+            Some(CloseReason::ServerRestarting) => {
+                "The server is restarting, reconnecting in a moment…"
+            }
+            Some(CloseReason::Other) | None => "The server closed the connection.",
+        }
+        .to_string()
+    }
+
+    /// Whether the game can be joined again after this close.
+    pub fn allows_reconnecting(&self) -> bool {
+        self.reason == Some(CloseReason::ServerRestarting)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct PlayerConnected {
-    message: String,
-    username: String,
+    pub message: String,
+    /// Identifies the player in the other messages, e.g. `TURN_CHANGED`. Left
+    /// out by the servers which identify the players by their username.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub player_id: String,
+    /// Name of the player, shown to the others.
+    pub username: String,
+}
+
+/// Sent when the connection of a player is lost. The player may reconnect
+/// while the game goes on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerDisconnected {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub player_id: String,
+    pub username: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerReconnected {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub player_id: String,
+    pub username: String,
+}
+
+/// Sent when a player left the round for good, on their own or kicked out by
+/// the creator of the round.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerLeft {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub player_id: String,
+    pub username: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub kicked: bool,
+}
+
+macro_rules! impl_presence_id {
+    ($($ty:ident),*) => {
+        $(
+            impl $ty {
+                /// The player ID of the player, or their username when the
+                /// server didn't send it.
+                pub fn id(&self) -> &str {
+                    if self.player_id.is_empty() {
+                        &self.username
+                    } else {
+                        &self.player_id
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_presence_id!(
+    PlayerConnected,
+    PlayerDisconnected,
+    PlayerReconnected,
+    PlayerLeft
+);
+
+/// Sent by the creator of the round to remove a player, who is sent a `CLOSE`
+/// message with the `KICKED` reason.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KickPlayer {
+    pub player_id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TurnChanged {
+    /// Player ID of the player whose turn it is.
+    pub player_id: String,
+    /// Number of seconds the player has to play, if the turn has a deadline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_limit: Option<u32>,
+    /// Scores of the players by player ID, for the games keeping scores.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub scores: BTreeMap<String, i64>,
+}
+
+/// Sent to a spectator once connected.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Spectating {
+    /// Player IDs of the players the spectator may watch the game as, seeing
+    /// their hidden cards. Spectators only see the public information of the
+    /// game when the server doesn't allow it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub seats: Vec<String>,
+}
+
+/// Sent by a spectator to watch the game as one of the allowed players, or
+/// to go back to the public view without a player ID. The server answers with
+/// the updates of the components whose visibility changed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WatchSeat {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct GameFinished {
-    winners: Vec<String>,
+    pub winners: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Error {
-    messages: Vec<String>,
+    pub messages: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct ActionAwaited {
-    all_of: Vec<AwaitedAction>,
+    pub all_of: Vec<AwaitedAction>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-#[serde(tag = "type")]
+#[serde(tag = "type", remote = "Self")]
 pub enum AwaitedAction {
     #[serde(rename = "OnClick")]
     OnClick { target_component: String },
+
+    /// Drag a component, usually a card, and drop it onto another one.
+    #[serde(rename = "OnDrop")]
+    OnDrop {
+        source_component: String,
+        target_component: String,
+    },
+
+    /// Select between `min` and `max` components among the given ones, e.g.
+    /// the cards to discard.
+    #[serde(rename = "SelectMany")]
+    SelectMany {
+        among_components: Vec<String>,
+        min: usize,
+        max: usize,
+    },
+
+    #[serde(skip)]
+    Unknown { r#type: String, raw: Value },
+}
+
+tolerant_tagged_enum!(AwaitedAction {
+    OnClick => "OnClick",
+    OnDrop => "OnDrop",
+    SelectMany => "SelectMany",
+});
+
+/// Sent by the client once the player did one of the awaited actions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PerformAction {
+    pub action: AwaitedAction,
+    /// The components chosen for a `SelectMany` action.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub selected_components: Vec<String>,
+}
+
+impl From<AwaitedAction> for PerformAction {
+    fn from(action: AwaitedAction) -> Self {
+        PerformAction {
+            action,
+            selected_components: Vec::new(),
+        }
+    }
+}
+
+/// Sent by the client to talk to the other players.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SendChatMessage {
+    pub text: String,
+    /// Everyone in the game receives the message when there is no target.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<ChatTarget>,
+}
+
+/// A chat message, as relayed by the server to its recipients.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChatMessage {
+    /// Player ID of the sender.
+    pub sender: String,
+    pub text: String,
+    /// When the server received the message, in milliseconds since the Unix
+    /// epoch.
+    pub timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<ChatTarget>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", remote = "Self")]
+pub enum ChatTarget {
+    /// The team of the sender.
+    #[serde(rename = "Team")]
+    Team,
+
+    #[serde(rename = "Player")]
+    Player { player_id: String },
+
+    #[serde(skip)]
+    Unknown { r#type: String, raw: Value },
+}
+
+tolerant_tagged_enum!(ChatTarget {
+    Team => "Team",
+    Player => "Player",
+});
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct InterfaceUpdate {
-    components: Vec<InterfaceComponent>,
+    pub components: Vec<InterfaceComponent>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct InterfaceComponent {
-    id: ComponentId,
-    position: ComponentPosition,
+    pub id: ComponentId,
+    pub position: ComponentPosition,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ComponentId(String);
 
+impl ComponentId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 impl<S: ToString> From<S> for ComponentId {
     fn from(id: S) -> Self {
         ComponentId(id.to_string())
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum ComponentPosition {
     Top,
@@ -105,20 +429,39 @@ pub enum ComponentPosition {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct ComponentsUpdates {
     #[serde(rename = "components")]
-    updates: Vec<ComponentUpdate>,
+    pub updates: Vec<ComponentUpdate>,
 }
 
 #[derive(Serialize, Deserialize, From, Clone, Debug, PartialEq)]
-#[serde(tag = "type")]
+#[serde(tag = "type", remote = "Self")]
 pub enum ComponentUpdate {
     Create {
         id: ComponentId,
         component: Component,
     },
+
+    /// Turns a card face up or face down. The identity of the card is sent when
+    /// it is revealed to players who didn't know it.
+    #[from(ignore)]
+    Flip {
+        id: ComponentId,
+        visibility: Visibility,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        card: Option<Box<Card>>,
+    },
+
+    #[serde(skip)]
+    #[from(ignore)]
+    Unknown { r#type: String, raw: Value },
 }
 
+tolerant_tagged_enum!(ComponentUpdate {
+    Create => "Create",
+    Flip => "Flip",
+});
+
 #[derive(Serialize, Deserialize, From, Clone, Debug, PartialEq)]
-#[serde(tag = "type")]
+#[serde(tag = "type", remote = "Self")]
 pub enum Component {
     #[serde(rename = "Card")]
     #[from]
@@ -127,26 +470,104 @@ pub enum Component {
     #[serde(rename = "Hand")]
     #[from]
     Hand(Hand),
+
+    #[serde(skip)]
+    Unknown { r#type: String, raw: Value },
 }
 
+tolerant_tagged_enum!(Component {
+    Card => "Card",
+    Hand => "Hand",
+});
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Card {
-    name: String,
-    description: String,
-    front_image: Option<String>,
-    back_image: Option<String>,
-    state: CardState,
+    /// The identity of the card is omitted when it is not visible to the player.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub front_image: Option<String>,
+    pub back_image: Option<String>,
+    #[serde(default)]
+    pub state: CardState,
+    /// Player ID of the owner of the card, defaults to the one of its hand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Visibility::is_everyone")]
+    pub visibility: Visibility,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+impl Card {
+    /// Whether the card tells what it is, i.e. it is not only a back.
+    pub fn has_identity(&self) -> bool {
+        !self.name.is_empty() || self.front_image.is_some() || self.state != CardState::default()
+    }
+}
+
+/// Who can see the face of a card, or the cards of a hand. Ordered from the
+/// most to the least restrictive.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Face down, nobody can see it.
+    Hidden,
+    /// Only visible to its owner.
+    Owner,
+    /// Face up.
+    Everyone,
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Visibility::Everyone
+    }
+}
+
+impl Visibility {
+    pub fn is_everyone(&self) -> bool {
+        *self == Visibility::Everyone
+    }
+}
+
+/// What is printed on a card. Cards of a standard deck only have a suit and a
+/// value, which were the only fields sent by the first servers, while other
+/// games describe their cards with arbitrary properties.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
 pub struct CardState {
-    suit: String,
-    value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, CardProperty>,
+}
+
+/// A property of a card, e.g. a cost, a color or a list of keywords.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum CardProperty {
+    Bool(bool),
+    /// Listed before `Float` so that integers, e.g. `7`, aren't sent back as `7.0`.
+    Integer(i64),
+    Float(f64),
+    /// A CSS color, sent as `{ "color": "#e02020" }` to tell it apart from text.
+    Color {
+        color: String,
+    },
+    Text(String),
+    List(Vec<CardProperty>),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Hand {
-    cards: Vec<ComponentId>,
+    pub cards: Vec<ComponentId>,
+    /// Player ID of the owner of the hand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Restricts the visibility of the cards of the hand.
+    #[serde(default, skip_serializing_if = "Visibility::is_everyone")]
+    pub visibility: Visibility,
 }
 
 #[cfg(test)]
@@ -164,6 +585,101 @@ mod tests {
 
         let decoded: Message = serde_json::from_str(as_str.as_ref()).expect("decoding failed");
         assert_eq!(decoded, msg, "decoding differs from expectation");
+
+        #[cfg(feature = "msgpack")]
+        test_binary_parity(&msg, encoded.len());
+    }
+
+    /// Checks that `type_tag` gives the tag written by serde, and that the
+    /// values cover the known types, in order.
+    fn test_type_tags<T: Serialize>(
+        values: &[T],
+        type_tag: impl Fn(&T) -> &str,
+        known_types: &[&str],
+    ) {
+        let tags = values
+            .iter()
+            .map(|value| {
+                let encoded = serde_json::to_value(value).expect("encoding failed");
+                assert_eq!(encoded["type"], type_tag(value));
+                type_tag(value)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tags, known_types);
+    }
+
+    fn decode_all<T: serde::de::DeserializeOwned>(json: &str) -> Vec<T> {
+        serde_json::from_str(json).expect("decoding failed")
+    }
+
+    #[test]
+    fn type_tags() {
+        test_type_tags(
+            &Message::examples(),
+            Message::type_tag,
+            Message::KNOWN_TYPES,
+        );
+        test_type_tags(
+            &decode_all::<AwaitedAction>(
+                r#"[
+                    { "type": "OnClick", "target_component": "c1" },
+                    { "type": "OnDrop", "source_component": "c1", "target_component": "c2" },
+                    { "type": "SelectMany", "among_components": ["c1"], "min": 1, "max": 1 }
+                ]"#,
+            ),
+            AwaitedAction::type_tag,
+            AwaitedAction::KNOWN_TYPES,
+        );
+        test_type_tags(
+            &decode_all::<ComponentUpdate>(
+                r#"[
+                    { "type": "Create", "id": "h1", "component": { "type": "Hand", "cards": [] } },
+                    { "type": "Flip", "id": "c1", "visibility": "everyone" }
+                ]"#,
+            ),
+            ComponentUpdate::type_tag,
+            ComponentUpdate::KNOWN_TYPES,
+        );
+        test_type_tags(
+            &decode_all::<Component>(
+                r#"[
+                    { "type": "Card", "front_image": null, "back_image": null },
+                    { "type": "Hand", "cards": [] }
+                ]"#,
+            ),
+            Component::type_tag,
+            Component::KNOWN_TYPES,
+        );
+        test_type_tags(
+            &decode_all::<ChatTarget>(
+                r#"[{ "type": "Team" }, { "type": "Player", "player_id": "p1" }]"#,
+            ),
+            ChatTarget::type_tag,
+            ChatTarget::KNOWN_TYPES,
+        );
+
+        let unknown: Message = serde_json::from_str(r#"{"type":"LATER"}"#).unwrap();
+        assert_eq!(unknown.type_tag(), "LATER");
+    }
+
+    /// Checks that the message round-trips identically in MessagePack, and that
+    /// it is not bigger than in JSON.
+    #[cfg(feature = "msgpack")]
+    fn test_binary_parity(msg: &Message, json_len: usize) {
+        let encoded = Encoding::MessagePack
+            .encode(msg)
+            .expect("binary encoding failed");
+        let decoded = Encoding::MessagePack
+            .decode(&encoded)
+            .expect("binary decoding failed");
+        assert_eq!(&decoded, msg, "binary decoding differs from JSON");
+
+        assert!(
+            encoded.len() <= json_len,
+            "binary encoding is bigger than JSON: {} > {}",
+            encoded.len(),
+            json_len
+        );
     }
 
     #[test]
@@ -178,7 +694,18 @@ mod tests {
 
     #[test]
     fn close() {
-        test_encoding_decoding(Message::Close, r#"{"type":"CLOSE"}"#);
+        test_encoding_decoding(Close::default(), r#"{"type":"CLOSE"}"#);
+        test_encoding_decoding(
+            Close {
+                reason: Some(CloseReason::ServerRestarting),
+                message: Some("Back in 5 minutes.".to_string()),
+            },
+            r#"{"type":"CLOSE","reason":"SERVER_RESTARTING","message":"Back in 5 minutes."}"#,
+        );
+
+        let decoded: Message =
+            serde_json::from_str(r#"{"type":"CLOSE","reason":"BANNED"}"#).expect("decoding failed");
+        assert_eq!(decoded, Close::new(CloseReason::Other).into());
     }
 
     #[test]
@@ -187,11 +714,117 @@ mod tests {
             PlayerConnected {
                 username: "Toto".to_string(),
                 message: "Say hello to Toto.".to_string(),
+                player_id: String::new(),
             },
             r#"{"type":"PLAYER_CONNECTED","message":"Say hello to Toto.","username":"Toto"}"#,
         );
     }
 
+    #[test]
+    fn player_ids() {
+        let connected = PlayerConnected {
+            message: "Say hello to Toto.".to_string(),
+            player_id: "p1".to_string(),
+            username: "Toto".to_string(),
+        };
+        test_encoding_decoding(
+            connected.clone(),
+            r#"{"type":"PLAYER_CONNECTED","message":"Say hello to Toto.","player_id":"p1","username":"Toto"}"#,
+        );
+        assert_eq!(connected.id(), "p1");
+
+        let left = PlayerLeft {
+            player_id: String::new(),
+            username: "Toto".to_string(),
+            kicked: false,
+        };
+        assert_eq!(left.id(), "Toto");
+    }
+
+    #[test]
+    fn player_disconnected_and_reconnected() {
+        test_encoding_decoding(
+            PlayerDisconnected {
+                username: "Toto".to_string(),
+                player_id: String::new(),
+            },
+            r#"{"type":"PLAYER_DISCONNECTED","username":"Toto"}"#,
+        );
+        test_encoding_decoding(
+            PlayerReconnected {
+                username: "Toto".to_string(),
+                player_id: String::new(),
+            },
+            r#"{"type":"PLAYER_RECONNECTED","username":"Toto"}"#,
+        );
+    }
+
+    #[test]
+    fn leave_and_kick() {
+        test_encoding_decoding(Message::LeaveRound, r#"{"type":"LEAVE_ROUND"}"#);
+        test_encoding_decoding(
+            KickPlayer {
+                player_id: "Toto".to_string(),
+            },
+            r#"{"type":"KICK_PLAYER","player_id":"Toto"}"#,
+        );
+        test_encoding_decoding(
+            PlayerLeft {
+                username: "Toto".to_string(),
+                kicked: true,
+                player_id: String::new(),
+            },
+            r#"{"type":"PLAYER_LEFT","username":"Toto","kicked":true}"#,
+        );
+        test_encoding_decoding(
+            PlayerLeft {
+                username: "Toto".to_string(),
+                kicked: false,
+                player_id: String::new(),
+            },
+            r#"{"type":"PLAYER_LEFT","username":"Toto"}"#,
+        );
+    }
+
+    #[test]
+    fn turn_changed() {
+        test_encoding_decoding(
+            TurnChanged {
+                player_id: "Toto".to_string(),
+                time_limit: None,
+                scores: BTreeMap::new(),
+            },
+            r#"{"type":"TURN_CHANGED","player_id":"Toto"}"#,
+        );
+        test_encoding_decoding(
+            TurnChanged {
+                player_id: "Toto".to_string(),
+                time_limit: Some(30),
+                scores: vec![("Titi".to_string(), -5), ("Toto".to_string(), 12)]
+                    .into_iter()
+                    .collect(),
+            },
+            r#"{
+                "type": "TURN_CHANGED",
+                "player_id": "Toto",
+                "time_limit": 30,
+                "scores": { "Titi": -5, "Toto": 12 }
+            }"#
+            .replace(|c: char| c.is_whitespace(), ""),
+        );
+    }
+
+    #[test]
+    fn spectating() {
+        test_encoding_decoding(
+            Spectating {
+                seats: vec!["Toto".to_string()],
+            },
+            r#"{"type":"SPECTATING","seats":["Toto"]}"#,
+        );
+        test_encoding_decoding(WatchSeat { player_id: None }, r#"{"type":"WATCH_SEAT"}"#);
+    }
+
     #[test]
     fn start_game() {
         test_encoding_decoding(Message::StartGame, r#"{"type":"START_GAME"}"#);
@@ -241,6 +874,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn perform_action() {
+        test_encoding_decoding(
+            PerformAction {
+                action: AwaitedAction::OnDrop {
+                    source_component: "c1".to_string(),
+                    target_component: "played_cards".to_string(),
+                },
+                selected_components: vec![],
+            },
+            r#"{
+                "type": "PERFORM_ACTION",
+                "action": {
+                    "type": "OnDrop",
+                    "source_component": "c1",
+                    "target_component": "played_cards"
+                }
+            }"#
+            .replace(|c: char| c.is_whitespace(), ""),
+        );
+    }
+
+    #[test]
+    fn perform_select_many() {
+        test_encoding_decoding(
+            PerformAction {
+                action: AwaitedAction::SelectMany {
+                    among_components: vec!["c1".to_string(), "c2".to_string(), "c3".to_string()],
+                    min: 1,
+                    max: 2,
+                },
+                selected_components: vec!["c1".to_string(), "c3".to_string()],
+            },
+            r#"{
+                "type": "PERFORM_ACTION",
+                "action": {
+                    "type": "SelectMany",
+                    "among_components": ["c1", "c2", "c3"],
+                    "min": 1,
+                    "max": 2
+                },
+                "selected_components": ["c1", "c3"]
+            }"#
+            .replace(|c: char| c.is_whitespace(), ""),
+        );
+    }
+
+    #[test]
+    fn send_chat_message() {
+        test_encoding_decoding(
+            SendChatMessage {
+                text: "gg".to_string(),
+                target: None,
+            },
+            r#"{"type":"SEND_CHAT_MESSAGE","text":"gg"}"#,
+        );
+        test_encoding_decoding(
+            SendChatMessage {
+                text: "Play a heart".to_string(),
+                target: Some(ChatTarget::Team),
+            },
+            r#"{"type":"SEND_CHAT_MESSAGE","text":"Play a heart","target":{"type":"Team"}}"#,
+        );
+    }
+
+    #[test]
+    fn chat_message() {
+        test_encoding_decoding(
+            ChatMessage {
+                sender: "alice".to_string(),
+                text: "Hi!".to_string(),
+                timestamp: 1_600_000_000_000,
+                target: Some(ChatTarget::Player {
+                    player_id: "bob".to_string(),
+                }),
+            },
+            r#"{
+                "type": "CHAT_MESSAGE",
+                "sender": "alice",
+                "text": "Hi!",
+                "timestamp": 1600000000000,
+                "target": { "type": "Player", "player_id": "bob" }
+            }"#
+            .replace(|c: char| c.is_whitespace(), ""),
+        );
+    }
+
     #[test]
     fn interface_update() {
         test_encoding_decoding(
@@ -286,6 +1006,8 @@ mod tests {
                                 "3a618ae83d664b43b1096738f559978a".into(),
                                 "bd5b40c1a4c342539dbc3165982ccf31".into(),
                             ],
+                            owner: None,
+                            visibility: Visibility::Everyone,
                         }
                         .into(),
                     },
@@ -297,9 +1019,12 @@ mod tests {
                             front_image: None,
                             back_image: None,
                             state: CardState {
-                                suit: "H".to_string(),
-                                value: "2".to_string(),
+                                suit: Some("H".to_string()),
+                                value: Some("2".to_string()),
+                                ..Default::default()
                             },
+                            owner: None,
+                            visibility: Visibility::Everyone,
                         }
                         .into(),
                     },
@@ -340,4 +1065,265 @@ mod tests {
             .replace(|c: char| c.is_whitespace(), ""),
         );
     }
+
+    #[test]
+    fn unknown_message() {
+        test_encoding_decoding(
+            Message::Unknown {
+                r#type: "SHUFFLE".to_string(),
+                raw: serde_json::json!({ "type": "SHUFFLE", "seed": 42 }),
+            },
+            r#"{"seed":42,"type":"SHUFFLE"}"#,
+        );
+    }
+
+    #[test]
+    fn unknown_awaited_action() {
+        test_encoding_decoding(
+            ActionAwaited {
+                all_of: vec![
+                    AwaitedAction::OnClick {
+                        target_component: "hand".to_string(),
+                    },
+                    AwaitedAction::Unknown {
+                        r#type: "OnDrag".to_string(),
+                        raw: serde_json::json!({ "type": "OnDrag", "from": "hand" }),
+                    },
+                ],
+            },
+            r#"{
+                "type": "ACTION_AWAITED",
+                "all_of": [
+                    {
+                        "type": "OnClick",
+                        "target_component": "hand"
+                    },
+                    {
+                        "from": "hand",
+                        "type": "OnDrag"
+                    }
+                ]
+            }"#
+            .replace(|c: char| c.is_whitespace(), ""),
+        );
+    }
+
+    #[test]
+    fn unknown_component_update_and_component() {
+        test_encoding_decoding(
+            ComponentsUpdates {
+                updates: vec![
+                    ComponentUpdate::Unknown {
+                        r#type: "Destroy".to_string(),
+                        raw: serde_json::json!({ "type": "Destroy", "id": "hand" }),
+                    },
+                    ComponentUpdate::Create {
+                        id: "deck".into(),
+                        component: Component::Unknown {
+                            r#type: "Deck".to_string(),
+                            raw: serde_json::json!({ "type": "Deck", "size": 52 }),
+                        },
+                    },
+                ],
+            },
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    {
+                        "id": "hand",
+                        "type": "Destroy"
+                    },
+                    {
+                        "type": "Create",
+                        "id": "deck",
+                        "component": {
+                            "size": 52,
+                            "type": "Deck"
+                        }
+                    }
+                ]
+            }"#
+            .replace(|c: char| c.is_whitespace(), ""),
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_ignored() {
+        let decoded: Message =
+            serde_json::from_str(r#"{"type":"GAME_FINISHED","winners":["Toto"],"duration":1234}"#)
+                .expect("decoding failed");
+        assert_eq!(
+            decoded,
+            GameFinished {
+                winners: vec!["Toto".to_string()],
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn missing_type_is_an_error() {
+        assert!(serde_json::from_str::<Message>(r#"{"winners":["Toto"]}"#).is_err());
+    }
+
+    #[test]
+    fn invalid_known_message_is_an_error() {
+        assert!(serde_json::from_str::<Message>(r#"{"type":"GAME_FINISHED"}"#).is_err());
+    }
+
+    #[test]
+    fn card_properties() {
+        let mut properties = BTreeMap::new();
+        properties.insert("cost".to_string(), CardProperty::Float(3.5));
+        properties.insert("wild".to_string(), CardProperty::Bool(true));
+        properties.insert(
+            "color".to_string(),
+            CardProperty::Color {
+                color: "#e02020".to_string(),
+            },
+        );
+        properties.insert(
+            "keywords".to_string(),
+            CardProperty::List(vec![
+                CardProperty::Text("haste".to_string()),
+                CardProperty::Float(2.0),
+            ]),
+        );
+        test_encoding_decoding(
+            ComponentsUpdates {
+                updates: vec![ComponentUpdate::Create {
+                    id: "c1".into(),
+                    component: Card {
+                        name: "Dragon".to_string(),
+                        description: "Fiery.".to_string(),
+                        front_image: None,
+                        back_image: None,
+                        state: CardState {
+                            properties,
+                            ..Default::default()
+                        },
+                        owner: None,
+                        visibility: Visibility::Everyone,
+                    }
+                    .into(),
+                }],
+            },
+            r##"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    {
+                        "type": "Create",
+                        "id": "c1",
+                        "component": {
+                            "type": "Card",
+                            "name": "Dragon",
+                            "description": "Fiery.",
+                            "front_image": null,
+                            "back_image": null,
+                            "state": {
+                                "properties": {
+                                    "color": { "color": "#e02020" },
+                                    "cost": 3.5,
+                                    "keywords": ["haste", 2.0],
+                                    "wild": true
+                                }
+                            }
+                        }
+                    }
+                ]
+            }"##
+            .replace(|c: char| c.is_whitespace(), ""),
+        );
+    }
+
+    #[test]
+    fn integer_card_property() {
+        let decoded: CardState =
+            serde_json::from_str(r#"{"properties":{"cost":3}}"#).expect("decoding failed");
+        assert_eq!(decoded.properties["cost"], CardProperty::Integer(3));
+        assert_eq!(
+            serde_json::to_string(&decoded).expect("encoding failed"),
+            r#"{"properties":{"cost":3}}"#
+        );
+    }
+
+    #[test]
+    fn hidden_cards_and_flips() {
+        test_encoding_decoding(
+            ComponentsUpdates {
+                updates: vec![
+                    ComponentUpdate::Create {
+                        id: "hand".into(),
+                        component: Hand {
+                            cards: vec!["c1".into()],
+                            owner: Some("p1".to_string()),
+                            visibility: Visibility::Owner,
+                        }
+                        .into(),
+                    },
+                    ComponentUpdate::Create {
+                        id: "c1".into(),
+                        component: Card {
+                            name: "".to_string(),
+                            description: "".to_string(),
+                            front_image: None,
+                            back_image: Some("back.png".to_string()),
+                            state: CardState::default(),
+                            owner: None,
+                            visibility: Visibility::Hidden,
+                        }
+                        .into(),
+                    },
+                    ComponentUpdate::Flip {
+                        id: "c1".into(),
+                        visibility: Visibility::Everyone,
+                        card: None,
+                    },
+                ],
+            },
+            r#"{
+                "type": "COMPONENTS_UPDATES",
+                "components": [
+                    {
+                        "type": "Create",
+                        "id": "hand",
+                        "component": {
+                            "type": "Hand",
+                            "cards": ["c1"],
+                            "owner": "p1",
+                            "visibility": "owner"
+                        }
+                    },
+                    {
+                        "type": "Create",
+                        "id": "c1",
+                        "component": {
+                            "type": "Card",
+                            "name": "",
+                            "description": "",
+                            "front_image": null,
+                            "back_image": "back.png",
+                            "state": {},
+                            "visibility": "hidden"
+                        }
+                    },
+                    {
+                        "type": "Flip",
+                        "id": "c1",
+                        "visibility": "everyone"
+                    }
+                ]
+            }"#
+            .replace(|c: char| c.is_whitespace(), ""),
+        );
+    }
+
+    #[test]
+    fn card_without_identity() {
+        let decoded: Card =
+            serde_json::from_str(r#"{"back_image":null,"front_image":null,"visibility":"hidden"}"#)
+                .expect("decoding failed");
+        assert!(!decoded.has_identity());
+        assert_eq!(decoded.visibility, Visibility::Hidden);
+    }
 }
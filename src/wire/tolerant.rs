@@ -0,0 +1,77 @@
+//! Tolerant decoding of the enums tagged by a `type` field.
+//!
+//! Serde fails to decode an internally tagged enum as soon as it meets a tag it
+//! doesn't know, which turns the whole message into an error whenever the
+//! server is more recent than the client. Instead, the tagged enums of the
+//! protocol are derived with `#[serde(remote = "Self")]`, which only generates
+//! inherent `serialize` and `deserialize` functions, and `tolerant_tagged_enum!`
+//! implements the serde traits on top of them. Values with an unknown tag are
+//! decoded into the `Unknown { r#type, raw }` variant of the enum, which keeps
+//! the raw value so that it can be logged, displayed and re-encoded as is.
+
+/// Implements `Serialize` and `Deserialize` for a tagged enum derived with
+/// `#[serde(tag = "type", remote = "Self")]` and having a skipped
+/// `Unknown { r#type: String, raw: Value }` variant. All the other variants
+/// must be listed with their tags.
+macro_rules! tolerant_tagged_enum {
+    ($ty:ident { $($variant:ident => $tag:literal),* $(,)? }) => {
+        impl $ty {
+            /// The tags understood by this version of the client.
+            pub const KNOWN_TYPES: &'static [&'static str] = &[$($tag),*];
+
+            /// Returns the tag of the value if it wasn't understood.
+            #[allow(unused)]
+            pub fn unknown_type(&self) -> Option<&str> {
+                match self {
+                    $ty::Unknown { r#type, .. } => Some(r#type),
+                    _ => None,
+                }
+            }
+
+            /// Returns the tag of the value, as written in its `type` field.
+            #[allow(unused)]
+            pub fn type_tag(&self) -> &str {
+                match self {
+                    $($ty::$variant { .. } => $tag,)*
+                    $ty::Unknown { r#type, .. } => r#type,
+                }
+            }
+        }
+
+        impl serde::Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                match self {
+                    $ty::Unknown { raw, .. } => serde::Serialize::serialize(raw, serializer),
+                    known => $ty::serialize(known, serializer),
+                }
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::de::Error as _;
+
+                let raw = <serde_json::Value as serde::Deserialize>::deserialize(deserializer)?;
+                let r#type = match raw.get("type").and_then(serde_json::Value::as_str) {
+                    Some(r#type) => r#type,
+                    None => return Err(D::Error::missing_field("type")),
+                };
+
+                if $ty::KNOWN_TYPES.contains(&r#type) {
+                    $ty::deserialize(raw).map_err(D::Error::custom)
+                } else {
+                    Ok($ty::Unknown {
+                        r#type: r#type.to_string(),
+                        raw,
+                    })
+                }
+            }
+        }
+    };
+}
@@ -14,7 +14,7 @@ function absPath(...pathSegments) {
 
 module.exports = (env, argv) => {
   const isProd = argv.mode === 'production';
-  const cargo_args = isProd ? '--features console_error_panic_hook' : '';
+  const cargo_args = isProd ? '--features console_error_panic_hook' : '--features debug-console';
 
   return {
     entry: {